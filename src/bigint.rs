@@ -0,0 +1,187 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{AddAssign, Mul, MulAssign};
+
+// Base 10^9 keeps decimal conversion trivial in both directions
+const BASE: u64 = 1_000_000_000;
+
+// Arbitrary-precision unsigned integer, little-endian limbs with no trailing zeros
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        BigUint { limbs: Vec::new() }
+    }
+
+    // Builds a number from decimal digits, most significant first
+    pub fn from_digits(digits: &[u32]) -> Self {
+        let mut limbs = Vec::with_capacity(digits.len() / 9 + 1);
+        for chunk in digits.rchunks(9) {
+            limbs.push(chunk.iter().fold(0, |acc, &d| acc * 10 + d));
+        }
+        let mut n = BigUint { limbs };
+        n.trim();
+        n
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        self.limbs
+            .iter()
+            .rev()
+            .try_fold(0u64, |acc, &limb| acc.checked_mul(BASE)?.checked_add(limb as u64))
+    }
+
+    pub fn pow(&self, mut exponent: u64) -> BigUint {
+        // Square and multiply
+        let mut result = BigUint::from(1);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result *= &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    fn trim(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(mut value: u64) -> Self {
+        let mut limbs = Vec::new();
+        while value > 0 {
+            limbs.push((value % BASE) as u32);
+            value /= BASE;
+        }
+        BigUint { limbs }
+    }
+}
+
+impl AddAssign<&BigUint> for BigUint {
+    fn add_assign(&mut self, other: &BigUint) {
+        if self.limbs.len() < other.limbs.len() {
+            self.limbs.resize(other.limbs.len(), 0);
+        }
+        let mut carry = 0;
+        for (i, limb) in self.limbs.iter_mut().enumerate() {
+            let sum = *limb as u64 + other.limbs.get(i).copied().unwrap_or(0) as u64 + carry;
+            *limb = (sum % BASE) as u32;
+            carry = sum / BASE;
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u32);
+        }
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        // Schoolbook multiplication; each partial product fits in a u64 before carrying
+        let mut limbs = vec![0u64; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let cur = limbs[i + j] + a as u64 * b as u64 + carry;
+                limbs[i + j] = cur % BASE;
+                carry = cur / BASE;
+            }
+            let mut k = i + other.limbs.len();
+            while carry > 0 {
+                let cur = limbs[k] + carry;
+                limbs[k] = cur % BASE;
+                carry = cur / BASE;
+                k += 1;
+            }
+        }
+        let mut n = BigUint {
+            limbs: limbs.into_iter().map(|l| l as u32).collect(),
+        };
+        n.trim();
+        n
+    }
+}
+
+impl MulAssign<&BigUint> for BigUint {
+    fn mul_assign(&mut self, other: &BigUint) {
+        *self = &*self * other;
+    }
+}
+
+impl std::iter::Sum for BigUint {
+    fn sum<I: Iterator<Item = BigUint>>(iter: I) -> Self {
+        iter.fold(BigUint::zero(), |mut acc, n| {
+            acc += &n;
+            acc
+        })
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.limbs.split_last() {
+            None => write!(f, "0"),
+            Some((most, rest)) => {
+                write!(f, "{}", most)?;
+                for limb in rest.iter().rev() {
+                    write!(f, "{:09}", limb)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_and_display() {
+        let mut n = BigUint::from(u64::MAX);
+        n += &BigUint::from(u64::MAX);
+        assert_eq!(n.to_string(), "36893488147419103230");
+        assert_eq!(BigUint::zero().to_string(), "0");
+        let digits = [0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 7];
+        assert_eq!(BigUint::from_digits(&digits).to_string(), "1000000007");
+        assert!(BigUint::from(1_000_000_000) > BigUint::from(999_999_999));
+    }
+
+    #[test]
+    fn test_mul_and_pow() {
+        let max = BigUint::from(u64::MAX);
+        assert_eq!((&max * &max).to_string(), "340282366920938463426481119284349108225");
+        assert_eq!(&max * &BigUint::zero(), BigUint::zero());
+        assert_eq!(BigUint::from(2).pow(100).to_string(), "1267650600228229401496703205376");
+        assert_eq!(BigUint::from(7).pow(0), BigUint::from(1));
+        assert_eq!(max.to_u64(), Some(u64::MAX));
+        assert_eq!((&max * &BigUint::from(2)).to_u64(), None);
+    }
+}
@@ -0,0 +1,82 @@
+// Small helpers for the per-day option lists passed after the day number,
+// e.g. `cargo run -- 2 report --limit 5`.
+
+// The mode is the first argument that is neither an option nor an option's value. Options
+// taking a value are listed in `values` and bare switches in `flags`; any other option, a
+// missing value or a second mode is an error.
+pub fn mode<'a>(day: u32, args: &'a [String], values: &[&str], flags: &[&str]) -> Option<&'a str> {
+    let fail = |message: String| -> ! {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    };
+    let mut mode = None;
+    let mut rest = args.iter().map(|s| s.as_str());
+    while let Some(arg) = rest.next() {
+        if values.contains(&arg) {
+            if rest.next().is_none() {
+                fail(format!("Missing value for {}", arg));
+            }
+        } else if flags.contains(&arg) {
+            continue;
+        } else if arg.starts_with("--") {
+            fail(format!("Unknown option for day {}: {}", day, arg));
+        } else if let Some(first) = mode {
+            fail(format!("Unexpected argument for day {} after {}: {}", day, first, arg));
+        } else {
+            mode = Some(arg);
+        }
+    }
+    mode
+}
+
+// For days without modes or options: anything passed is an error
+pub fn no_options(day: u32, args: &[String]) {
+    if let Some(mode) = mode(day, args, &[], &[]) {
+        unknown_mode(day, mode);
+    }
+}
+
+pub fn option_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == name)
+        .and_then(|i| args.get(i + 1))
+        .map(|s| s.as_str())
+}
+
+pub fn parse_option<T: std::str::FromStr>(args: &[String], name: &str, default: T) -> T {
+    match option_value(args, name) {
+        Some(value) => value.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid value for {}: {}", name, value);
+            std::process::exit(1);
+        }),
+        None => default,
+    }
+}
+
+pub fn has_flag(args: &[String], name: &str) -> bool {
+    args.iter().any(|a| a == name)
+}
+
+pub fn unknown_mode(day: u32, mode: &str) -> ! {
+    eprintln!("Unknown mode for day {}: {}", day, mode);
+    std::process::exit(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mode_skips_options() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let values = ["--part", "--limit"];
+        let flags = ["--merge"];
+        let mode_of = |list: &[&str]| mode(2, &args(list), &values, &flags).map(String::from);
+
+        assert_eq!(mode_of(&["--merge", "report", "--limit", "5"]), Some("report".into()));
+        assert_eq!(mode_of(&["--part", "1", "report"]), Some("report".into()));
+        // An option's value is never taken as the mode, even when it looks like one
+        assert_eq!(mode_of(&["--limit", "report"]), None);
+        assert_eq!(mode_of(&[]), None);
+    }
+}
@@ -26,7 +26,7 @@ fn part1(input: &str) -> u32 {
         match direction {
             "R" => position = (position + distance) % 100,
             "L" => {
-                position = position - distance;
+                position -= distance;
                 while position < 0 {
                     position += 100;
                 }
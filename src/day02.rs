@@ -1,15 +1,55 @@
+use crate::cli;
+use crate::interval::IntervalSet;
 use std::fs;
 
-pub fn solve() {
-    let input = fs::read_to_string("inputs/day02.txt").expect("Failed to read inputs/day02.txt");
+pub fn run(args: &[String]) {
+    let mode = cli::mode(2, args, &["--part", "--limit"], &["--merge"]);
+    let merge = cli::has_flag(args, "--merge");
+    match mode {
+        None => solve(merge),
+        Some("report") => {
+            let ranges = load_ranges(merge);
+            let part = cli::parse_option(args, "--part", 2);
+            let limit = cli::parse_option(args, "--limit", 10);
+            print_report(&ranges, part == 2, limit);
+        }
+        Some(mode) => cli::unknown_mode(2, mode),
+    }
+}
+
+fn solve(merge: bool) {
+    let ranges = load_ranges(merge);
 
-    let part1_answer = part1(&input);
+    let part1_answer = part1(&ranges);
     println!("Part 1: {}", part1_answer);
 
-    let part2_answer = part2(&input);
+    let part2_answer = part2(&ranges);
     println!("Part 2: {}", part2_answer);
 }
 
+fn load_ranges(merge: bool) -> Vec<(u64, u64)> {
+    let input = fs::read_to_string("inputs/day02.txt").expect("Failed to read inputs/day02.txt");
+    let parsed = parse_ranges(&input);
+
+    // Bad entries are skipped, but never silently
+    for problem in &parsed.problems {
+        eprintln!("Warning: {}", problem);
+    }
+
+    if merge {
+        return merge_ranges(&parsed.ranges);
+    }
+
+    let overlaps = count_overlaps(&parsed.ranges);
+    if overlaps > 0 {
+        eprintln!(
+            "Warning: {} overlapping range(s), some IDs are counted more than once (use --merge)",
+            overlaps
+        );
+    }
+    parsed.ranges
+}
+
 fn is_invalid_id(n: u64) -> bool {
     // An invalid ID is a number where some sequence of digits is repeated twice
     // e.g., 55 = "5" + "5", 6464 = "64" + "64", 123123 = "123" + "123"
@@ -19,7 +59,7 @@ fn is_invalid_id(n: u64) -> bool {
     let len = s.len();
 
     // Must have even number of digits
-    if len % 2 != 0 {
+    if !len.is_multiple_of(2) {
         return false;
     }
 
@@ -34,35 +74,112 @@ fn is_invalid_id(n: u64) -> bool {
     first_half == second_half
 }
 
-fn part1(input: &str) -> u64 {
-    let mut sum: u64 = 0;
+struct RangeProblem {
+    line: usize,
+    column: usize,
+    entry: String,
+    reason: &'static str,
+}
 
-    // Parse ranges like "11-22,95-115,998-1012" or one per line
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
+impl std::fmt::Display for RangeProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "line {}, column {}: {} \"{}\"", self.line, self.column, self.reason, self.entry)
+    }
+}
+
+struct ParsedRanges {
+    ranges: Vec<(u64, u64)>,
+    problems: Vec<RangeProblem>,
+}
 
-        // Split by comma for multiple ranges on one line
-        for range_str in line.split(',') {
-            let range_str = range_str.trim();
+fn parse_ranges(input: &str) -> ParsedRanges {
+    let mut ranges: Vec<(u64, u64)> = Vec::new();
+    let mut problems = Vec::new();
+
+    // Parse ranges like "11-22,95-115,998-1012" or one per line
+    for (line_idx, line) in input.lines().enumerate() {
+        // Split by comma for multiple ranges on one line, tracking where each entry starts
+        let mut offset = 0;
+        for raw in line.split(',') {
+            let column = offset + (raw.len() - raw.trim_start().len()) + 1;
+            offset += raw.len() + 1;
+
+            let range_str = raw.trim();
             if range_str.is_empty() {
                 continue;
             }
 
+            let mut problem = |reason| {
+                problems.push(RangeProblem {
+                    line: line_idx + 1,
+                    column,
+                    entry: range_str.to_string(),
+                    reason,
+                })
+            };
+
             let parts: Vec<&str> = range_str.split('-').collect();
             if parts.len() != 2 {
+                problem("expected START-END");
                 continue;
             }
 
-            let start: u64 = parts[0].trim().parse().expect("Failed to parse range start");
-            let end: u64 = parts[1].trim().parse().expect("Failed to parse range end");
-
-            for n in start..=end {
-                if is_invalid_id(n) {
-                    sum += n;
+            let (start, end) = match (parts[0].trim().parse(), parts[1].trim().parse()) {
+                (Ok(start), Ok(end)) => (start, end),
+                _ => {
+                    problem("invalid number in range");
+                    continue;
                 }
+            };
+
+            if start > end {
+                problem("reversed range");
+                continue;
+            }
+
+            ranges.push((start, end));
+        }
+    }
+
+    ParsedRanges { ranges, problems }
+}
+
+fn merge_ranges(ranges: &[(u64, u64)]) -> Vec<(u64, u64)> {
+    let merged: IntervalSet<u64> = ranges.iter().copied().collect();
+    merged.iter().collect()
+}
+
+fn count_overlaps(ranges: &[(u64, u64)]) -> usize {
+    // Number of ranges that start inside an earlier (sorted) range
+    let mut sorted = ranges.to_vec();
+    sorted.sort();
+
+    let mut overlaps = 0;
+    let mut furthest_end = None;
+    for (start, end) in sorted {
+        if furthest_end.is_some_and(|e| start <= e) {
+            overlaps += 1;
+        }
+        furthest_end = Some(furthest_end.map_or(end, |e: u64| e.max(end)));
+    }
+    overlaps
+}
+
+fn part1(ranges: &[(u64, u64)]) -> u64 {
+    sum_invalid(ranges, is_invalid_id)
+}
+
+fn part2(ranges: &[(u64, u64)]) -> u64 {
+    sum_invalid(ranges, is_invalid_id_v2)
+}
+
+fn sum_invalid(ranges: &[(u64, u64)], is_invalid: fn(u64) -> bool) -> u64 {
+    let mut sum: u64 = 0;
+
+    for &(start, end) in ranges {
+        for n in start..=end {
+            if is_invalid(n) {
+                sum += n;
             }
         }
     }
@@ -86,7 +203,7 @@ fn is_invalid_id_v2(n: u64) -> bool {
     // (pattern must repeat at least twice, so max pattern length is len/2)
     for pattern_len in 1..=len / 2 {
         // Length must be divisible by pattern length
-        if len % pattern_len != 0 {
+        if !len.is_multiple_of(pattern_len) {
             continue;
         }
 
@@ -109,38 +226,77 @@ fn is_invalid_id_v2(n: u64) -> bool {
     false
 }
 
-fn part2(input: &str) -> u64 {
-    let mut sum: u64 = 0;
-
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
+fn repeat_pattern(n: u64, any_repeat: bool) -> Option<(String, usize)> {
+    // Returns the repeated unit and how many times it repeats, using the
+    // shortest unit that matches. Part 1 rules only allow exactly two copies.
+    let s = n.to_string();
+    let len = s.len();
 
-        for range_str in line.split(',') {
-            let range_str = range_str.trim();
-            if range_str.is_empty() {
-                continue;
-            }
+    if !any_repeat {
+        return if is_invalid_id(n) {
+            Some((s[..len / 2].to_string(), 2))
+        } else {
+            None
+        };
+    }
 
-            let parts: Vec<&str> = range_str.split('-').collect();
-            if parts.len() != 2 {
-                continue;
-            }
+    let bytes = s.as_bytes();
+    (1..=len / 2)
+        .filter(|&pattern_len| len.is_multiple_of(pattern_len))
+        .find(|&pattern_len| bytes.chunks(pattern_len).all(|c| c == &bytes[..pattern_len]))
+        .map(|pattern_len| (s[..pattern_len].to_string(), len / pattern_len))
+}
 
-            let start: u64 = parts[0].trim().parse().expect("Failed to parse range start");
-            let end: u64 = parts[1].trim().parse().expect("Failed to parse range end");
+struct RangeReport {
+    start: u64,
+    end: u64,
+    count: usize,
+    sum: u64,
+    // (id, repeated unit, repeat count), capped at the listing limit
+    ids: Vec<(u64, String, usize)>,
+}
 
+fn build_report(ranges: &[(u64, u64)], any_repeat: bool, limit: usize) -> Vec<RangeReport> {
+    ranges
+        .iter()
+        .map(|&(start, end)| {
+            let mut report = RangeReport {
+                start,
+                end,
+                count: 0,
+                sum: 0,
+                ids: Vec::new(),
+            };
             for n in start..=end {
-                if is_invalid_id_v2(n) {
-                    sum += n;
+                if let Some((pattern, repeats)) = repeat_pattern(n, any_repeat) {
+                    report.count += 1;
+                    report.sum += n;
+                    if report.ids.len() < limit {
+                        report.ids.push((n, pattern, repeats));
+                    }
                 }
             }
+            report
+        })
+        .collect()
+}
+
+fn print_report(ranges: &[(u64, u64)], any_repeat: bool, limit: usize) {
+    let reports = build_report(ranges, any_repeat, limit);
+
+    for r in &reports {
+        println!("{}-{}: {} invalid, sum {}", r.start, r.end, r.count, r.sum);
+        for (id, pattern, repeats) in &r.ids {
+            println!("  {} = \"{}\" x {}", id, pattern, repeats);
+        }
+        if r.count > r.ids.len() {
+            println!("  ... and {} more", r.count - r.ids.len());
         }
     }
 
-    sum
+    let total_count: usize = reports.iter().map(|r| r.count).sum();
+    let total_sum: u64 = reports.iter().map(|r| r.sum).sum();
+    println!("Total: {} invalid, sum {}", total_count, total_sum);
 }
 
 #[cfg(test)]
@@ -172,7 +328,7 @@ mod tests {
 
         let input = "11-22";
         // 11, 22 are invalid -> 33
-        assert_eq!(part1(input), 11 + 22);
+        assert_eq!(part1(&parse_ranges(input).ranges), 11 + 22);
     }
 
     #[test]
@@ -203,10 +359,57 @@ mod tests {
         // From puzzle example:
         // 95-115 has 99 and 111
         let input = "95-115";
-        assert_eq!(part2(input), 99 + 111);
+        assert_eq!(part2(&parse_ranges(input).ranges), 99 + 111);
 
         // 998-1012 has 999 and 1010
         let input2 = "998-1012";
-        assert_eq!(part2(input2), 999 + 1010);
+        assert_eq!(part2(&parse_ranges(input2).ranges), 999 + 1010);
+    }
+
+    #[test]
+    fn test_report() {
+        let reports = build_report(&[(11, 22), (95, 115)], true, 1);
+        assert_eq!(reports.len(), 2);
+        assert_eq!((reports[0].count, reports[0].sum), (2, 33));
+        assert_eq!(reports[0].ids, vec![(11, "1".to_string(), 2)]);
+        assert_eq!((reports[1].count, reports[1].sum), (2, 99 + 111));
+
+        assert_eq!(repeat_pattern(1212121212, true), Some(("12".to_string(), 5)));
+        assert_eq!(repeat_pattern(222222, true), Some(("2".to_string(), 6)));
+        assert_eq!(repeat_pattern(222222, false), Some(("222".to_string(), 2)));
+        assert_eq!(repeat_pattern(111, false), None);
+    }
+
+    #[test]
+    fn test_range_validation() {
+        let parsed = parse_ranges("11-22, 30-20,abc\n5-x,95-115-3");
+        assert_eq!(parsed.ranges, vec![(11, 22)]);
+        let found: Vec<(usize, usize, &str)> = parsed
+            .problems
+            .iter()
+            .map(|p| (p.line, p.column, p.reason))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, 8, "reversed range"),
+                (1, 14, "expected START-END"),
+                (2, 1, "invalid number in range"),
+                (2, 5, "expected START-END"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_ranges() {
+        let ranges = [(95, 115), (11, 22), (20, 30), (31, 40), (100, 110)];
+        assert_eq!(count_overlaps(&ranges), 2);
+        let merged = merge_ranges(&ranges);
+        assert_eq!(merged, vec![(11, 40), (95, 115)]);
+        assert_eq!(count_overlaps(&merged), 0);
+
+        // 11 and 22 would be counted twice without merging
+        assert_eq!(sum_invalid(&[(11, 22), (11, 22)], is_invalid_id), 66);
+        assert_eq!(sum_invalid(&merge_ranges(&[(11, 22), (11, 22)]), is_invalid_id), 33);
     }
 }
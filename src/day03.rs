@@ -1,104 +1,233 @@
+use crate::bigint::BigUint;
+use crate::cli;
+use std::collections::VecDeque;
 use std::fs;
+use std::io::IsTerminal;
+
+pub fn run(args: &[String]) {
+    let mode = cli::mode(3, args, &["--digits", "--gap"], &["--min", "--allow-leading-zero"]);
+    if mode == Some("max") && cli::has_flag(args, "--min") {
+        eprintln!("--min can't be combined with the max mode");
+        std::process::exit(1);
+    }
+    let rule = Rule {
+        k: cli::parse_option(args, "--digits", 12),
+        gap: cli::parse_option(args, "--gap", 1).max(1),
+        minimize: mode == Some("min") || cli::has_flag(args, "--min"),
+        allow_leading_zero: cli::has_flag(args, "--allow-leading-zero"),
+    };
+    match mode {
+        Some("render") => render(&read_input(), &rule, std::io::stdout().is_terminal()),
+        // Without a mode, any rule option means a single custom total instead of both parts
+        Some("max") | Some("min") | None if !args.is_empty() => {
+            let input = read_input();
+            println!("{} digits: {}", rule.k, total_joltage(&input, &rule));
+        }
+        Some("count") => print_counts(&read_input(), &rule),
+        None => solve(),
+        Some(mode) => cli::unknown_mode(3, mode),
+    }
+}
+
+fn read_input() -> String {
+    fs::read_to_string("inputs/day03.txt").expect("Failed to read inputs/day03.txt")
+}
 
 pub fn solve() {
-    let input = fs::read_to_string("inputs/day03.txt").expect("Failed to read inputs/day03.txt");
+    let input = read_input();
 
-    let part1_answer = part1(&input);
+    let part1_answer: BigUint = banks(&input).map(|bank| max_joltage_k(bank, 2).value).sum();
     println!("Part 1: {}", part1_answer);
 
-    let part2_answer = part2(&input);
+    let part2_answer: BigUint = banks(&input).map(|bank| max_joltage_k(bank, 12).value).sum();
     println!("Part 2: {}", part2_answer);
 }
 
-fn max_joltage(bank: &str) -> u32 {
-    // Find the maximum two-digit joltage by picking two battery positions
-    // The joltage is formed by the digits at those positions in their original order
-
-    let digits: Vec<u32> = bank
-        .chars()
+fn parse_bank(bank: &str) -> Vec<u32> {
+    bank.chars()
         .filter(|c| c.is_ascii_digit())
         .map(|c| c.to_digit(10).unwrap())
-        .collect();
+        .collect()
+}
+
+fn banks(input: &str) -> impl Iterator<Item = &str> {
+    input.lines().map(|line| line.trim()).filter(|line| !line.is_empty())
+}
+
+struct Rule {
+    k: usize,
+    // Chosen positions must be at least this far apart (1 = no constraint)
+    gap: usize,
+    minimize: bool,
+    // Only matters when minimizing; a maximal selection may still start with a zero
+    allow_leading_zero: bool,
+}
+
+impl Rule {
+    fn max(k: usize) -> Self {
+        Rule {
+            k,
+            gap: 1,
+            minimize: false,
+            allow_leading_zero: false,
+        }
+    }
 
-    let len = digits.len();
-    if len < 2 {
-        return 0;
+    fn better(&self, a: u32, b: u32) -> bool {
+        if self.minimize {
+            a < b
+        } else {
+            a > b
+        }
     }
+}
 
-    // Precompute max digit from position i to end (suffix max)
-    let mut max_suffix = vec![0u32; len];
-    max_suffix[len - 1] = digits[len - 1];
-    for i in (0..len - 1).rev() {
-        max_suffix[i] = max_suffix[i + 1].max(digits[i]);
+fn select_positions(digits: &[u32], rule: &Rule) -> Option<Vec<usize>> {
+    // Greedy digit by digit: the j-th pick is the best digit whose position still leaves room
+    // for the remaining picks, taking the leftmost on ties so later picks keep the most options.
+    // Both ends of that window only move right, so a monotonic deque answers every window in
+    // amortized O(1) and the whole selection is O(n).
+    let n = digits.len();
+    let k = rule.k;
+    if k == 0 {
+        return Some(Vec::new());
     }
+    // The picks span (k - 1) * gap positions, which may not even fit in a usize
+    if (k - 1).checked_mul(rule.gap).is_none_or(|span| span >= n) {
+        return None;
+    }
+
+    let mut positions = Vec::with_capacity(k);
+    let mut window: VecDeque<usize> = VecDeque::new();
+    let mut next = 0;
+    let mut lo = 0;
+
+    for j in 0..k {
+        let hi = (k - 1 - j).checked_mul(rule.gap).and_then(|span| (n - 1).checked_sub(span))?;
+
+        let pick = if j == 0 && rule.minimize && !rule.allow_leading_zero {
+            // Zeros can't lead but may be picked later, so keep them out of the deque
+            let mut best: Option<usize> = None;
+            for p in (0..=hi).filter(|&p| digits[p] != 0) {
+                if best.is_none_or(|b| rule.better(digits[p], digits[b])) {
+                    best = Some(p);
+                }
+            }
+            best?
+        } else {
+            next = next.max(lo);
+            while next <= hi {
+                let d = digits[next];
+                while window.back().is_some_and(|&b| rule.better(d, digits[b])) {
+                    window.pop_back();
+                }
+                window.push_back(next);
+                next += 1;
+            }
+            while window.front().is_some_and(|&f| f < lo) {
+                window.pop_front();
+            }
+            *window.front()?
+        };
 
-    // For each position i as the tens digit, find the max joltage
-    let mut max_jolt = 0;
-    for i in 0..len - 1 {
-        let joltage = 10 * digits[i] + max_suffix[i + 1];
-        max_jolt = max_jolt.max(joltage);
+        positions.push(pick);
+        lo = pick.saturating_add(rule.gap);
     }
 
-    max_jolt
+    Some(positions)
 }
 
-fn part1(input: &str) -> u32 {
-    input
-        .lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
-        .map(|bank| max_joltage(bank))
-        .sum()
+struct Selection {
+    // Indices into the bank's digits, in increasing order
+    positions: Vec<usize>,
+    value: BigUint,
 }
 
-fn max_joltage_12(bank: &str) -> u64 {
-    // Find the maximum 12-digit joltage by picking exactly 12 battery positions
-    // Greedy algorithm: for each position, pick the maximum digit from valid range
+fn select_joltage(bank: &str, rule: &Rule) -> Selection {
+    // Banks with no valid selection (too short, or all zeros) contribute 0
+    let digits = parse_bank(bank);
+    let positions = select_positions(&digits, rule).unwrap_or_default();
+    let chosen: Vec<u32> = positions.iter().map(|&i| digits[i]).collect();
+    Selection {
+        positions,
+        value: BigUint::from_digits(&chosen),
+    }
+}
 
-    let digits: Vec<u32> = bank
-        .chars()
-        .filter(|c| c.is_ascii_digit())
-        .map(|c| c.to_digit(10).unwrap())
+// The puzzle's rule: the largest k-digit value, no gaps, no leading zero
+fn max_joltage_k(bank: &str, k: usize) -> Selection {
+    select_joltage(bank, &Rule::max(k))
+}
+
+fn total_joltage(input: &str, rule: &Rule) -> BigUint {
+    banks(input).map(|bank| select_joltage(bank, rule).value).sum()
+}
+
+fn count_optimal(bank: &str, rule: &Rule) -> BigUint {
+    // Number of distinct position sets that produce the optimal value under the rule
+    let digits = parse_bank(bank);
+    let target: Vec<u32> = match select_positions(&digits, rule) {
+        Some(positions) if !positions.is_empty() => {
+            positions.iter().map(|&i| digits[i]).collect()
+        }
+        _ => return BigUint::zero(),
+    };
+
+    // ways[p] = selections matching target[..=j] whose last pick is at p
+    let matches = |p: usize, j: usize| digits[p] == target[j];
+    let mut ways: Vec<BigUint> = (0..digits.len())
+        .map(|p| BigUint::from(matches(p, 0) as u64))
         .collect();
 
-    let n = digits.len();
-    if n < 12 {
-        return 0;
-    }
-
-    let mut result: u64 = 0;
-    let mut prev_pos: i32 = -1;
-
-    for i in 0..12 {
-        // For digit i, we can pick from positions (prev_pos+1) to (n-12+i)
-        // This ensures we have enough positions left for remaining digits
-        let start = (prev_pos + 1) as usize;
-        let end = n - 12 + i; // inclusive
-
-        // Find the maximum digit in this range (pick leftmost if tied)
-        let mut max_val = 0;
-        let mut max_pos = start;
-        for p in start..=end {
-            if digits[p] > max_val {
-                max_val = digits[p];
-                max_pos = p;
+    for j in 1..target.len() {
+        let mut next = vec![BigUint::zero(); digits.len()];
+        let mut reachable = BigUint::zero();
+        for p in 0..digits.len() {
+            if p >= rule.gap {
+                reachable += &ways[p - rule.gap];
+            }
+            if matches(p, j) {
+                next[p] = reachable.clone();
             }
         }
+        ways = next;
+    }
 
-        result = result * 10 + max_val as u64;
-        prev_pos = max_pos as i32;
+    ways.into_iter().sum()
+}
+
+fn print_counts(input: &str, rule: &Rule) {
+    let mut total = BigUint::zero();
+    for bank in banks(input) {
+        let count = count_optimal(bank, rule);
+        println!("{} -> {} ({} selections)", bank, select_joltage(bank, rule).value, count);
+        total += &count;
     }
+    println!("Total selections: {}", total);
+}
 
-    result
+fn highlight_bank(bank: &str, positions: &[usize], ansi: bool) -> String {
+    let mut out = String::new();
+    let mut chosen = positions.iter().peekable();
+    for (i, c) in bank.chars().filter(|c| c.is_ascii_digit()).enumerate() {
+        if chosen.next_if_eq(&&i).is_some() {
+            if ansi {
+                out.push_str(&format!("\x1b[1;32m{}\x1b[0m", c));
+            } else {
+                out.push_str(&format!("[{}]", c));
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
 }
 
-fn part2(input: &str) -> u64 {
-    input
-        .lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
-        .map(|bank| max_joltage_12(bank))
-        .sum()
+fn render(input: &str, rule: &Rule, ansi: bool) {
+    for bank in banks(input) {
+        let selection = select_joltage(bank, rule);
+        println!("{} -> {}", highlight_bank(bank, &selection.positions, ansi), selection.value);
+    }
 }
 
 #[cfg(test)]
@@ -107,29 +236,89 @@ mod tests {
 
     #[test]
     fn test_max_joltage() {
-        assert_eq!(max_joltage("987654321111111"), 98);
-        assert_eq!(max_joltage("811111111111119"), 89);
-        assert_eq!(max_joltage("234234234234278"), 78);
-        assert_eq!(max_joltage("818181911112111"), 92);
+        assert_eq!(max_joltage_k("987654321111111", 2).value, BigUint::from(98));
+        assert_eq!(max_joltage_k("811111111111119", 2).value, BigUint::from(89));
+        assert_eq!(max_joltage_k("234234234234278", 2).value, BigUint::from(78));
+        assert_eq!(max_joltage_k("818181911112111", 2).value, BigUint::from(92));
     }
 
     #[test]
     fn test_example() {
         let input = "987654321111111\n811111111111119\n234234234234278\n818181911112111";
-        assert_eq!(part1(input), 357);
+        assert_eq!(total_joltage(input, &Rule::max(2)), BigUint::from(357));
     }
 
     #[test]
     fn test_max_joltage_12() {
-        assert_eq!(max_joltage_12("987654321111111"), 987654321111);
-        assert_eq!(max_joltage_12("811111111111119"), 811111111119);
-        assert_eq!(max_joltage_12("234234234234278"), 434234234278);
-        assert_eq!(max_joltage_12("818181911112111"), 888911112111);
+        assert_eq!(max_joltage_k("987654321111111", 12).value, BigUint::from(987654321111));
+        assert_eq!(max_joltage_k("811111111111119", 12).value, BigUint::from(811111111119));
+        assert_eq!(max_joltage_k("234234234234278", 12).value, BigUint::from(434234234278));
+        assert_eq!(max_joltage_k("818181911112111", 12).value, BigUint::from(888911112111));
     }
 
     #[test]
     fn test_part2_example() {
         let input = "987654321111111\n811111111111119\n234234234234278\n818181911112111";
-        assert_eq!(part2(input), 3121910778619);
+        assert_eq!(total_joltage(input, &Rule::max(12)), BigUint::from(3121910778619));
+    }
+
+    #[test]
+    fn test_max_joltage_k_beyond_u64() {
+        let bank = "9".repeat(10) + &"1".repeat(20) + &"8".repeat(15);
+        let expected = "9".repeat(10) + "11111" + &"8".repeat(15);
+        assert_eq!(max_joltage_k(&bank, 30).value.to_string(), expected);
+        assert_eq!(max_joltage_k("12", 3).value, BigUint::zero());
+    }
+
+    #[test]
+    fn test_max_joltage_with_zeros() {
+        assert_eq!(max_joltage_k("005", 2).value, BigUint::from(5));
+        assert_eq!(max_joltage_k("005", 2).positions, vec![0, 2]);
+        assert_eq!(max_joltage_k("000", 2).value, BigUint::zero());
+        assert_eq!(max_joltage_k("000", 2).positions, vec![0, 1]);
+        assert_eq!(max_joltage_k("10203", 3).value, BigUint::from(203));
+    }
+
+    #[test]
+    fn test_selected_positions() {
+        let selection = max_joltage_k("818181911112111", 12);
+        assert_eq!(selection.positions, vec![0, 2, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14]);
+        let pair = max_joltage_k("818181911112111", 2);
+        assert_eq!(pair.positions, vec![6, 11]);
+        let rendered = highlight_bank("818181911112111", &pair.positions, false);
+        assert_eq!(rendered, "818181[9]1111[2]111");
+    }
+
+    #[test]
+    fn test_min_and_gap_rules() {
+        let min = |k, allow_leading_zero| Rule {
+            k,
+            gap: 1,
+            minimize: true,
+            allow_leading_zero,
+        };
+        assert_eq!(select_joltage("3102045", &min(3, false)).value, BigUint::from(100));
+        assert_eq!(select_joltage("3102045", &min(3, true)).value, BigUint::from(4));
+        assert_eq!(select_joltage("3102045", &min(3, true)).positions, vec![2, 4, 5]);
+        assert!(select_positions(&[0, 0, 0], &min(2, false)).is_none());
+
+        let gapped = Rule { gap: 2, ..Rule::max(3) };
+        // 9s at 0 and 1 can't both be taken
+        assert_eq!(select_joltage("99181", &gapped).positions, vec![0, 2, 4]);
+        assert_eq!(select_joltage("99181", &gapped).value, BigUint::from(911));
+        assert!(select_positions(&[1, 2, 3, 4], &gapped).is_none());
+
+        let huge = |k| Rule { gap: usize::MAX, ..Rule::max(k) };
+        assert!(select_positions(&[1, 2, 3], &huge(3)).is_none());
+        assert_eq!(select_joltage("132", &huge(1)).value, BigUint::from(3));
+    }
+
+    #[test]
+    fn test_count_optimal() {
+        assert_eq!(count_optimal("987654321111111", &Rule::max(2)), BigUint::from(1));
+        assert_eq!(count_optimal("91111", &Rule::max(2)), BigUint::from(4));
+        assert_eq!(count_optimal("919", &Rule::max(2)), BigUint::from(1));
+        assert_eq!(count_optimal("1111", &Rule::max(2)), BigUint::from(6));
+        assert_eq!(count_optimal("1111", &Rule { gap: 2, ..Rule::max(2) }), BigUint::from(3));
     }
 }
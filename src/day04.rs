@@ -1,108 +1,394 @@
+use crate::cli;
+use crate::grid::{Grid, Pos, ADJACENT, ORTHOGONAL};
 use std::fs;
+use std::str::FromStr;
+use std::time::Instant;
 
-pub fn solve() {
+pub fn run(args: &[String]) {
+    let mode = cli::mode(
+        4,
+        args,
+        &["--neighborhood", "--threshold", "--ppm", "--scale", "--size", "--density"],
+        &["--wrap"],
+    );
+    let rules = Rules::new(
+        cli::parse_option(args, "--neighborhood", Neighborhood::Moore),
+        cli::parse_option(args, "--threshold", 4),
+        cli::has_flag(args, "--wrap"),
+    );
+    match mode {
+        None => solve(&rules),
+        Some("rounds") => {
+            let rounds = erode(&load_grid(&rules), &rules).rounds;
+            for (i, removed) in rounds.iter().enumerate() {
+                println!("Round {}: {} removed", i + 1, removed);
+            }
+            println!("Total: {}", rounds.iter().sum::<usize>());
+        }
+        Some("layers") => {
+            let grid = load_grid(&rules);
+            print!("{}", render_layers(&grid, &erode(&grid, &rules)));
+        }
+        Some("frames") => {
+            let grid = load_grid(&rules);
+            let erosion = erode(&grid, &rules);
+            match cli::option_value(args, "--ppm") {
+                Some(dir) => {
+                    let scale = cli::parse_option(args, "--scale", 4);
+                    write_ppm_frames(&grid, &erosion, dir, scale);
+                }
+                None => print_frames(&grid, &erosion),
+            }
+        }
+        Some("bench") => {
+            let size = cli::parse_option(args, "--size", 1000);
+            let density = cli::parse_option(args, "--density", 0.7);
+            bench(size, density, &rules);
+        }
+        Some(mode) => cli::unknown_mode(4, mode),
+    }
+}
+
+fn load_grid(rules: &Rules) -> Grid<bool> {
     let input = fs::read_to_string("inputs/day04.txt").expect("Failed to read inputs/day04.txt");
+    let grid = parse_rolls(&input);
+    if rules.wrap && rules.neighborhood == Neighborhood::Hex && grid.height() % 2 == 1 {
+        eprintln!("Hex neighborhoods can only wrap on grids with an even number of rows");
+        std::process::exit(1);
+    }
+    grid
+}
 
-    let part1_answer = part1(&input);
+fn solve(rules: &Rules) {
+    let grid = load_grid(rules);
+
+    let part1_answer = part1(&grid, rules);
     println!("Part 1: {}", part1_answer);
 
-    let part2_answer = part2(&input);
+    let part2_answer = part2(&grid, rules);
     println!("Part 2: {}", part2_answer);
 }
 
-fn part1(input: &str) -> usize {
-    let grid: Vec<Vec<char>> = input
-        .lines()
-        .map(|line| line.chars().collect())
-        .collect();
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Neighborhood {
+    VonNeumann,
+    Moore,
+    // Every cell within Chebyshev distance r
+    Radius(usize),
+    // Hexagonal cells in "odd-r" layout: odd rows are shifted half a cell right
+    Hex,
+}
+
+impl FromStr for Neighborhood {
+    type Err = String;
 
-    let rows = grid.len();
-    if rows == 0 {
-        return 0;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "von-neumann" => Ok(Neighborhood::VonNeumann),
+            "moore" => Ok(Neighborhood::Moore),
+            "hex" => Ok(Neighborhood::Hex),
+            _ => s
+                .strip_prefix("radius:")
+                .and_then(|r| r.parse().ok())
+                .map(Neighborhood::Radius)
+                .ok_or_else(|| format!("unknown neighborhood: {}", s)),
+        }
     }
-    let cols = grid[0].len();
+}
 
-    let mut count = 0;
+struct Rules {
+    neighborhood: Neighborhood,
+    // A roll is accessible with fewer than this many neighboring rolls
+    threshold: usize,
+    // Toroidal edges instead of a bounded grid
+    wrap: bool,
+    // Neighbor offsets for even and odd rows (they only differ for hex)
+    offsets: [Vec<(isize, isize)>; 2],
+    // Largest row or column distance of any offset
+    reach: usize,
+}
 
-    for r in 0..rows {
-        for c in 0..cols {
-            if grid[r][c] == '@' {
-                // Count adjacent @ symbols in all 8 directions
-                let adjacent = count_adjacent(&grid, r, c, rows, cols);
-                if adjacent < 4 {
-                    count += 1;
-                }
+impl Rules {
+    fn new(neighborhood: Neighborhood, threshold: usize, wrap: bool) -> Self {
+        let offsets = match neighborhood {
+            Neighborhood::VonNeumann => [ORTHOGONAL.to_vec(), ORTHOGONAL.to_vec()],
+            Neighborhood::Moore => [ADJACENT.to_vec(), ADJACENT.to_vec()],
+            Neighborhood::Radius(r) => {
+                let r = r as isize;
+                let square: Vec<(isize, isize)> = (-r..=r)
+                    .flat_map(|dr| (-r..=r).map(move |dc| (dr, dc)))
+                    .filter(|&d| d != (0, 0))
+                    .collect();
+                [square.clone(), square]
             }
+            Neighborhood::Hex => [
+                vec![(-1, -1), (-1, 0), (0, -1), (0, 1), (1, -1), (1, 0)],
+                vec![(-1, 0), (-1, 1), (0, -1), (0, 1), (1, 0), (1, 1)],
+            ],
+        };
+        let reach = offsets
+            .iter()
+            .flatten()
+            .map(|&(dr, dc)| dr.unsigned_abs().max(dc.unsigned_abs()))
+            .max()
+            .unwrap_or(0);
+        Rules {
+            neighborhood,
+            threshold,
+            wrap,
+            offsets,
+            reach,
         }
     }
 
-    count
+    fn neighbors<'a>(&'a self, grid: &'a Grid<bool>, pos: Pos) -> impl Iterator<Item = Pos> + 'a {
+        let offsets = &self.offsets[pos.0 % 2];
+        // On a grid narrower than the neighborhood, wrapping can land several offsets on the
+        // same cell or on pos itself; each cell still counts once and never as its own neighbor
+        let span = 2 * self.reach + 1;
+        let undersized = grid.width() < span || grid.height() < span;
+        offsets.iter().enumerate().filter_map(move |(i, &d)| {
+            if !self.wrap {
+                return grid.offset(pos, d);
+            }
+            let n = grid.wrapping_offset(pos, d);
+            let repeated = || {
+                n == pos || offsets[..i].iter().any(|&e| grid.wrapping_offset(pos, e) == n)
+            };
+            if undersized && repeated() {
+                None
+            } else {
+                Some(n)
+            }
+        })
+    }
+
+    fn accessible(&self, adjacent: usize) -> bool {
+        adjacent < self.threshold
+    }
 }
 
-fn count_adjacent(grid: &[Vec<char>], r: usize, c: usize, rows: usize, cols: usize) -> usize {
-    let directions: [(i32, i32); 8] = [
-        (-1, -1), (-1, 0), (-1, 1),
-        (0, -1),           (0, 1),
-        (1, -1),  (1, 0),  (1, 1),
-    ];
+impl Default for Rules {
+    // The puzzle's rules: fewer than 4 of the 8 surrounding cells, bounded grid
+    fn default() -> Self {
+        Rules::new(Neighborhood::Moore, 4, false)
+    }
+}
 
-    let mut count = 0;
-    for (dr, dc) in directions {
-        let nr = r as i32 + dr;
-        let nc = c as i32 + dc;
+fn parse_rolls(input: &str) -> Grid<bool> {
+    Grid::parse(input, |c| c == '@')
+}
 
-        if nr >= 0 && nr < rows as i32 && nc >= 0 && nc < cols as i32 {
-            if grid[nr as usize][nc as usize] == '@' {
-                count += 1;
-            }
-        }
-    }
+fn part1(grid: &Grid<bool>, rules: &Rules) -> usize {
+    grid.positions()
+        .filter(|&pos| grid[pos] && rules.accessible(count_adjacent(grid, pos, rules)))
+        .count()
+}
+
+fn count_adjacent(grid: &Grid<bool>, pos: Pos, rules: &Rules) -> usize {
+    rules.neighbors(grid, pos).filter(|&n| grid[n]).count()
+}
 
-    count
+fn part2(grid: &Grid<bool>, rules: &Rules) -> usize {
+    erode(grid, rules).rounds.iter().sum()
 }
 
-fn part2(input: &str) -> usize {
-    let mut grid: Vec<Vec<char>> = input
-        .lines()
-        .map(|line| line.chars().collect())
-        .collect();
+struct Erosion {
+    // Rolls removed in each round
+    rounds: Vec<usize>,
+    // Round (1-based) in which each roll was removed, 0 for survivors and empty cells
+    removed_in: Grid<usize>,
+}
 
-    let rows = grid.len();
-    if rows == 0 {
-        return 0;
+fn erode(grid: &Grid<bool>, rules: &Rules) -> Erosion {
+    // Removes accessible rolls round by round and returns how many went in each round.
+    // Neighbor counts only ever drop, so a roll can only become accessible in the round
+    // after one of its neighbors is removed: those are the only cells worth re-checking.
+    // This relies on every neighborhood being symmetric.
+    let mut present = grid.clone();
+    let mut removed_in = Grid::new(grid.width(), grid.height(), 0);
+    let mut counts = Grid::new(grid.width(), grid.height(), 0usize);
+    for pos in grid.positions().filter(|&pos| grid[pos]) {
+        counts[pos] = count_adjacent(grid, pos, rules);
     }
-    let cols = grid[0].len();
 
-    let mut total_removed = 0;
+    // Round in which each cell was last queued, so a cell is only queued once per round
+    let mut queued_in = Grid::new(grid.width(), grid.height(), 0usize);
+    let mut frontier: Vec<Pos> = grid.positions().filter(|&pos| grid[pos]).collect();
+    let mut rounds = Vec::new();
 
-    loop {
-        // Find all accessible rolls (fewer than 4 adjacent)
-        let mut to_remove = Vec::new();
-
-        for r in 0..rows {
-            for c in 0..cols {
-                if grid[r][c] == '@' {
-                    let adjacent = count_adjacent(&grid, r, c, rows, cols);
-                    if adjacent < 4 {
-                        to_remove.push((r, c));
+    while !frontier.is_empty() {
+        let round = rounds.len() + 1;
+
+        // Decide the whole round from counts at its start, then apply it
+        let removed: Vec<Pos> = frontier
+            .into_iter()
+            .filter(|&pos| present[pos] && rules.accessible(counts[pos]))
+            .collect();
+        if removed.is_empty() {
+            break;
+        }
+        for &pos in &removed {
+            present[pos] = false;
+            removed_in[pos] = round;
+        }
+
+        frontier = Vec::new();
+        for &pos in &removed {
+            for n in rules.neighbors(grid, pos) {
+                if present[n] {
+                    counts[n] -= 1;
+                    if rules.accessible(counts[n]) && queued_in[n] != round {
+                        queued_in[n] = round;
+                        frontier.push(n);
                     }
                 }
             }
         }
 
+        rounds.push(removed.len());
+    }
+
+    Erosion { rounds, removed_in }
+}
+
+fn layer_glyph(round: usize) -> char {
+    // 1-9, then a-z, then A-Z covers 61 rounds; anything later shares '+'
+    match round {
+        1..=9 => char::from(b'0' + round as u8),
+        10..=35 => char::from(b'a' + (round - 10) as u8),
+        36..=61 => char::from(b'A' + (round - 36) as u8),
+        _ => '+',
+    }
+}
+
+fn render_layers(grid: &Grid<bool>, erosion: &Erosion) -> String {
+    let mut cells = Grid::new(grid.width(), grid.height(), '.');
+    for (pos, _) in grid.iter().filter(|(_, &roll)| roll) {
+        let round = erosion.removed_in[pos];
+        cells[pos] = if round == 0 { '@' } else { layer_glyph(round) };
+    }
+    cells.render(|&c| c)
+}
+
+// State of the pile at the start of the given round: rolls removed during that round are
+// still there and marked, earlier ones are gone
+#[derive(Clone, Copy, PartialEq)]
+enum FrameCell {
+    Empty,
+    Roll,
+    Removing,
+}
+
+fn frame(grid: &Grid<bool>, erosion: &Erosion, round: usize) -> Grid<FrameCell> {
+    let mut cells = Grid::new(grid.width(), grid.height(), FrameCell::Empty);
+    for (pos, _) in grid.iter().filter(|(_, &roll)| roll) {
+        let removed_in = erosion.removed_in[pos];
+        cells[pos] = if removed_in == round {
+            FrameCell::Removing
+        } else if removed_in == 0 || removed_in > round {
+            FrameCell::Roll
+        } else {
+            FrameCell::Empty
+        };
+    }
+    cells
+}
+
+fn print_frames(grid: &Grid<bool>, erosion: &Erosion) {
+    // One extra frame at the end shows what survives
+    for round in 1..=erosion.rounds.len() + 1 {
+        match erosion.rounds.get(round - 1) {
+            Some(removed) => println!("Round {} ({} removed):", round, removed),
+            None => println!("Final:"),
+        }
+        let text = frame(grid, erosion, round).render(|cell| match cell {
+            FrameCell::Empty => '.',
+            FrameCell::Roll => '@',
+            FrameCell::Removing => 'x',
+        });
+        println!("{}", text);
+    }
+}
+
+fn write_ppm_frames(grid: &Grid<bool>, erosion: &Erosion, dir: &str, scale: usize) {
+    fs::create_dir_all(dir).expect("Failed to create frame directory");
+    let frames = erosion.rounds.len() + 1;
+    for round in 1..=frames {
+        let image = frame(grid, erosion, round).to_ppm(scale, |cell| match cell {
+            FrameCell::Empty => [24, 24, 32],
+            FrameCell::Roll => [220, 220, 200],
+            FrameCell::Removing => [220, 40, 40],
+        });
+        let path = format!("{}/frame_{:03}.ppm", dir, round);
+        fs::write(&path, image).unwrap_or_else(|e| panic!("Failed to write {}: {}", path, e));
+    }
+    println!("Wrote {} frames to {}", frames, dir);
+}
+
+fn erode_naive(grid: &Grid<bool>, rules: &Rules) -> Vec<usize> {
+    // Reference implementation: rescan the whole grid every round
+    let mut grid = grid.clone();
+    let mut rounds = Vec::new();
+
+    loop {
+        // Find all accessible rolls
+        let to_remove: Vec<Pos> = grid
+            .positions()
+            .filter(|&pos| grid[pos] && rules.accessible(count_adjacent(&grid, pos, rules)))
+            .collect();
+
         if to_remove.is_empty() {
             break;
         }
 
         // Remove all accessible rolls
-        for (r, c) in &to_remove {
-            grid[*r][*c] = '.';
+        for &pos in &to_remove {
+            grid[pos] = false;
         }
 
-        total_removed += to_remove.len();
+        rounds.push(to_remove.len());
     }
 
-    total_removed
+    rounds
+}
+
+fn generate_grid(size: usize, density: f64, seed: u64) -> Grid<bool> {
+    // xorshift64, good enough for benchmark data
+    let mut state = seed.max(1);
+    let mut grid = Grid::new(size, size, false);
+    for pos in grid.positions().collect::<Vec<_>>() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        grid[pos] = (state % 1_000_000) as f64 / 1_000_000.0 < density;
+    }
+    grid
+}
+
+fn bench(size: usize, density: f64, rules: &Rules) {
+    let grid = generate_grid(size, density, 0x2025_1204);
+    println!("{}x{} grid, density {}", size, size, density);
+
+    let start = Instant::now();
+    let frontier = erode(&grid, rules).rounds;
+    println!(
+        "Frontier: {} removed in {} rounds, {:?}",
+        frontier.iter().sum::<usize>(),
+        frontier.len(),
+        start.elapsed()
+    );
+
+    let start = Instant::now();
+    let naive = erode_naive(&grid, rules);
+    println!(
+        "Naive:    {} removed in {} rounds, {:?}",
+        naive.iter().sum::<usize>(),
+        naive.len(),
+        start.elapsed()
+    );
+
+    assert_eq!(frontier, naive, "frontier and naive erosion disagree");
 }
 
 #[cfg(test)]
@@ -112,6 +398,81 @@ mod tests {
     #[test]
     fn test_part2_example() {
         let input = "..@@.@@@@.\n@@@.@.@.@@\n@@@@@.@.@@\n@.@@@@..@.\n@@.@@@@.@@\n.@@@@@@@.@\n.@.@.@.@@@\n@.@@@.@@@@\n.@@@@@@@@.\n@.@.@@@.@.";
-        assert_eq!(part2(input), 43);
+        assert_eq!(part2(&parse_rolls(input), &Rules::default()), 43);
+    }
+
+    #[test]
+    fn test_erosion_rounds() {
+        let input = "..@@.@@@@.\n@@@.@.@.@@\n@@@@@.@.@@\n@.@@@@..@.\n@@.@@@@.@@\n.@@@@@@@.@\n.@.@.@.@@@\n@.@@@.@@@@\n.@@@@@@@@.\n@.@.@@@.@.";
+        let grid = parse_rolls(input);
+        let rules = Rules::default();
+        assert_eq!(erode(&grid, &rules).rounds, vec![13, 12, 7, 5, 2, 1, 1, 1, 1]);
+        assert_eq!(erode_naive(&grid, &rules), erode(&grid, &rules).rounds);
+
+        for (seed, density) in [(1, 0.5), (2, 0.7), (3, 0.85)] {
+            let grid = generate_grid(60, density, seed);
+            assert_eq!(erode(&grid, &rules).rounds, erode_naive(&grid, &rules));
+        }
+    }
+
+    #[test]
+    fn test_neighborhood_rules() {
+        // A plus sign: the center has 4 orthogonal neighbors, the arms have 1 each
+        let grid = parse_rolls(".@.\n@@@\n.@.");
+        let moore = Rules::default();
+        assert_eq!(count_adjacent(&grid, (1, 1), &moore), 4);
+        assert_eq!(count_adjacent(&grid, (0, 1), &moore), 3);
+        let von_neumann = Rules::new(Neighborhood::VonNeumann, 2, false);
+        assert_eq!(count_adjacent(&grid, (0, 1), &von_neumann), 1);
+        assert_eq!(part1(&grid, &von_neumann), 4);
+
+        // Wrapping connects the top arm to the bottom arm
+        let wrapped = Rules::new(Neighborhood::VonNeumann, 2, true);
+        assert_eq!(count_adjacent(&grid, (0, 1), &wrapped), 2);
+        assert_eq!(part1(&grid, &wrapped), 0);
+
+        // Wrapping on grids smaller than the neighborhood counts each cell once
+        let row = parse_rolls("@@@\n");
+        let moore = Rules::new(Neighborhood::Moore, 4, true);
+        let mut seen: Vec<Pos> = moore.neighbors(&row, (0, 1)).collect();
+        seen.sort();
+        assert_eq!(seen, vec![(0, 0), (0, 2)]);
+        let radius = Rules::new("radius:2".parse().unwrap(), 30, true);
+        assert_eq!(count_adjacent(&parse_rolls("@@@\n@@@\n"), (0, 0), &radius), 5);
+
+        let radius2 = Rules::new("radius:2".parse().unwrap(), 30, false);
+        assert_eq!(count_adjacent(&grid, (0, 0), &radius2), 5);
+        assert_eq!(radius2.offsets[0].len(), 24);
+        assert_eq!(Rules::new(Neighborhood::Hex, 4, false).offsets[1].len(), 6);
+        assert!("triangle".parse::<Neighborhood>().is_err());
+
+        for rules in [von_neumann, wrapped, radius2, Rules::new(Neighborhood::Hex, 3, true)] {
+            for seed in 1..4 {
+                let grid = generate_grid(30, 0.6, seed);
+                assert_eq!(erode(&grid, &rules).rounds, erode_naive(&grid, &rules));
+            }
+        }
+    }
+
+    #[test]
+    fn test_layers_and_frames() {
+        // Bars erode from both ends towards the middle
+        let grid = parse_rolls("@@@@@\n.....\n@@@..");
+        let erosion = erode(&grid, &Rules::new(Neighborhood::VonNeumann, 2, false));
+        assert_eq!(render_layers(&grid, &erosion), "12321\n.....\n121..\n");
+
+        let block = parse_rolls("@@@\n@@@\n@@@");
+        assert_eq!(render_layers(&block, &erode(&block, &Rules::default())), "121\n232\n121\n");
+        let square = parse_rolls("@@\n@@");
+        let erosion_square = erode(&square, &Rules::new(Neighborhood::VonNeumann, 2, false));
+        assert_eq!(render_layers(&square, &erosion_square), "@@\n@@\n");
+
+        let first = frame(&grid, &erosion, 1);
+        assert!(first[(0, 0)] == FrameCell::Removing && first[(0, 2)] == FrameCell::Roll);
+        let last = frame(&grid, &erosion, 2);
+        assert!(last[(0, 0)] == FrameCell::Empty && last[(0, 2)] == FrameCell::Roll);
+
+        assert_eq!(layer_glyph(10), 'a');
+        assert_eq!(layer_glyph(61), 'Z');
     }
 }
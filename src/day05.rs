@@ -1,65 +1,371 @@
+use crate::cli;
+use crate::interval::IntervalSet;
 use std::fs;
+use std::io::{self, BufRead, BufReader};
 
-pub fn solve() {
+pub fn run(args: &[String]) {
+    let mode = cli::mode(5, args, &["--ids", "--top", "--margin"], &[]);
+    match mode {
+        None => solve(),
+        Some("stream") => {
+            let ranges = load_inventory().ranges;
+            let source = cli::option_value(args, "--ids").unwrap_or("-");
+            let counts = if source == "-" {
+                stream_ids(&ranges, io::stdin().lock())
+            } else {
+                let file = fs::File::open(source).unwrap_or_else(|e| {
+                    eprintln!("Failed to open {}: {}", source, e);
+                    std::process::exit(1);
+                });
+                stream_ids(&ranges, BufReader::new(file))
+            };
+            print_stream_counts(&ranges, &counts);
+        }
+        Some("report") => {
+            let top = cli::parse_option(args, "--top", 5);
+            let margin = cli::parse_option(args, "--margin", 1000);
+            print_report(&load_inventory(), top, margin);
+        }
+        Some(mode) => cli::unknown_mode(5, mode),
+    }
+}
+
+fn load_inventory() -> Inventory {
     let input = fs::read_to_string("inputs/day05.txt").expect("Failed to read input file");
+    parse_input(&input).unwrap_or_else(|e| {
+        eprintln!("inputs/day05.txt: {}", e);
+        std::process::exit(1);
+    })
+}
 
-    let (ranges, ids) = parse_input(&input);
+pub fn solve() {
+    let Inventory { ranges, ids } = load_inventory();
+    let fresh: IntervalSet<u64> = ranges.iter().copied().collect();
 
     // Part 1: Count fresh ingredients
-    let fresh_count = ids.iter().filter(|&&id| is_fresh(id, &ranges)).count();
-    println!("Part 1: {}", fresh_count);
+    match ids {
+        Some(ids) => {
+            let fresh_count = ids.iter().filter(|&&id| fresh.contains(id)).count();
+            println!("Part 1: {}", fresh_count);
+        }
+        None => println!("Part 1: not applicable (no ID section)"),
+    }
 
     // Part 2: Count total unique fresh IDs across all ranges
-    let total_fresh = count_unique_fresh_ids(&ranges);
+    let total_fresh = fresh.total_len();
     println!("Part 2: {}", total_fresh);
 }
 
-fn parse_input(input: &str) -> (Vec<(u64, u64)>, Vec<u64>) {
-    let parts: Vec<&str> = input.split("\n\n").collect();
+struct Inventory {
+    ranges: Vec<(u64, u64)>,
+    // None when the input has no ID section at all
+    ids: Option<Vec<u64>>,
+}
+
+#[derive(Debug, PartialEq)]
+struct ParseError {
+    line: usize,
+    message: String,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+fn parse_input(input: &str) -> Result<Inventory, ParseError> {
+    // Sections are separated by one or more blank lines. lines() already drops the \r of
+    // CRLF endings, and trimming takes care of stray whitespace.
+    let mut sections: Vec<Vec<(usize, &str)>> = Vec::new();
+    let mut in_section = false;
+    for (idx, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            in_section = false;
+            continue;
+        }
+        if !in_section {
+            sections.push(Vec::new());
+            in_section = true;
+        }
+        sections.last_mut().unwrap().push((idx + 1, line));
+    }
+
+    if let Some(extra) = sections.get(2) {
+        return Err(ParseError {
+            line: extra[0].0,
+            message: "unexpected third section".to_string(),
+        });
+    }
+    let mut sections = sections.into_iter();
+
+    let mut ranges = Vec::new();
+    for (line_no, line) in sections.next().unwrap_or_default() {
+        let error = |message: &str| ParseError {
+            line: line_no,
+            message: format!("{} \"{}\"", message, line),
+        };
+        let (start, end) = line.split_once('-').ok_or_else(|| error("expected START-END, got"))?;
+        let start: u64 = start.trim().parse().map_err(|_| error("invalid range start in"))?;
+        let end: u64 = end.trim().parse().map_err(|_| error("invalid range end in"))?;
+        if start > end {
+            return Err(error("range start is after its end in"));
+        }
+        ranges.push((start, end));
+    }
+
+    let ids = sections
+        .next()
+        .map(|section| {
+            section
+                .into_iter()
+                .map(|(line_no, line)| {
+                    line.parse().map_err(|_| ParseError {
+                        line: line_no,
+                        message: format!("invalid ID \"{}\"", line),
+                    })
+                })
+                .collect::<Result<Vec<u64>, _>>()
+        })
+        .transpose()?;
+
+    Ok(Inventory { ranges, ids })
+}
+
+// Ranges wholly inside another range, as (contained, container) indices into ranges.
+// Of two identical ranges, the later one is reported as redundant.
+fn redundant_ranges(ranges: &[(u64, u64)]) -> Vec<(usize, usize)> {
+    // Sorted by start, widest first: a range is contained exactly when something
+    // before it in this order reaches at least as far
+    let mut order: Vec<usize> = (0..ranges.len()).collect();
+    order.sort_by_key(|&i| (ranges[i].0, std::cmp::Reverse(ranges[i].1), i));
+
+    let mut redundant = Vec::new();
+    let mut widest: Option<usize> = None;
+    for i in order {
+        match widest {
+            Some(w) if ranges[w].1 >= ranges[i].1 => redundant.push((i, w)),
+            _ => widest = Some(i),
+        }
+    }
+    redundant.sort();
+    redundant
+}
 
-    let ranges: Vec<(u64, u64)> = parts[0]
-        .lines()
-        .map(|line| {
-            let nums: Vec<u64> = line.split('-').map(|n| n.parse().unwrap()).collect();
-            (nums[0], nums[1])
+// Query IDs outside every range but within margin of one, closest first
+fn near_misses(fresh: &IntervalSet<u64>, ids: &[u64], margin: u64) -> Vec<(u64, u64, (u64, u64))> {
+    let mut misses: Vec<(u64, u64, (u64, u64))> = ids
+        .iter()
+        .filter_map(|&id| {
+            let (before, after) = fresh.neighbors(id);
+            let below = before.map(|r| (id - r.1, r));
+            let above = after.map(|r| (r.0 - id, r));
+            let (distance, range) = match (below, above) {
+                (Some(b), Some(a)) => b.min(a),
+                (b, a) => b.or(a)?,
+            };
+            (distance <= margin).then_some((id, distance, range))
         })
         .collect();
+    misses.sort_by_key(|&(id, distance, _)| (distance, id));
+    misses
+}
 
-    let ids: Vec<u64> = parts[1]
-        .lines()
-        .filter(|line| !line.is_empty())
-        .map(|line| line.parse().unwrap())
-        .collect();
+fn print_report(inventory: &Inventory, top: usize, margin: u64) {
+    let ranges = &inventory.ranges;
+    let fresh: IntervalSet<u64> = ranges.iter().copied().collect();
+
+    println!("Ranges: {}", ranges.len());
+    println!("Merged intervals: {}", fresh.interval_count());
+    println!("Fresh IDs: {}", fresh.total_len());
+
+    let redundant = redundant_ranges(ranges);
+    println!("\nRedundant ranges: {}", redundant.len());
+    for &(inner, outer) in &redundant {
+        let (a, b) = (ranges[inner], ranges[outer]);
+        println!("  {}-{} is inside {}-{}", a.0, a.1, b.0, b.1);
+    }
+
+    let mut gaps: Vec<(u64, u64)> = fresh.gaps().collect();
+    gaps.sort_by_key(|&(start, end)| (std::cmp::Reverse(end - start), start));
+    println!("\nLargest gaps ({} total):", gaps.len());
+    for (start, end) in gaps.iter().take(top) {
+        println!("  {}-{} ({} IDs)", start, end, end - start + 1);
+    }
 
-    (ranges, ids)
+    match &inventory.ids {
+        Some(ids) => {
+            let misses = near_misses(&fresh, ids, margin);
+            println!("\nIDs within {} of a range: {}", margin, misses.len());
+            for (id, distance, (start, end)) in misses.iter().take(top) {
+                println!("  {} is {} away from {}-{}", id, distance, start, end);
+            }
+        }
+        None => println!("\nNo ID section, skipping near misses"),
+    }
 }
 
-fn is_fresh(id: u64, ranges: &[(u64, u64)]) -> bool {
-    ranges.iter().any(|&(start, end)| id >= start && id <= end)
+// Splits the number line at every range boundary, so each elementary segment is covered
+// by the same set of input ranges throughout. Counting hits per segment is enough to
+// recover per-range counts afterwards, even when ranges overlap.
+struct SegmentIndex {
+    // Segment i covers bounds[i]..bounds[i + 1] (exclusive end; None = through u64::MAX)
+    bounds: Vec<u64>,
 }
 
-fn count_unique_fresh_ids(ranges: &[(u64, u64)]) -> u64 {
-    if ranges.is_empty() {
-        return 0;
+impl SegmentIndex {
+    fn new(ranges: &[(u64, u64)]) -> Self {
+        let mut bounds: Vec<u64> = ranges
+            .iter()
+            .flat_map(|&(start, end)| [Some(start), end.checked_add(1)])
+            .flatten()
+            .collect();
+        bounds.sort_unstable();
+        bounds.dedup();
+        SegmentIndex { bounds }
     }
 
-    // Sort ranges by start value
-    let mut sorted: Vec<(u64, u64)> = ranges.to_vec();
-    sorted.sort_by_key(|r| r.0);
+    fn segment(&self, id: u64) -> Option<usize> {
+        self.bounds.partition_point(|&b| b <= id).checked_sub(1)
+    }
+}
+
+struct StreamCounts {
+    fresh: u64,
+    spoiled: u64,
+    invalid: u64,
+    // Hits per input range, in input order
+    per_range: Vec<u64>,
+}
+
+// Only the first few bad lines of a dump are reported individually
+const MAX_WARNINGS: u64 = 10;
 
-    // Merge overlapping ranges
-    let mut merged: Vec<(u64, u64)> = vec![sorted[0]];
-    for &(start, end) in &sorted[1..] {
-        let last = merged.last_mut().unwrap();
-        if start <= last.1 + 1 {
-            // Overlapping or adjacent, extend the range
-            last.1 = last.1.max(end);
+fn stream_ids(ranges: &[(u64, u64)], mut reader: impl BufRead) -> StreamCounts {
+    let fresh_set: IntervalSet<u64> = ranges.iter().copied().collect();
+    let index = SegmentIndex::new(ranges);
+    let mut segment_hits = vec![0u64; index.bounds.len()];
+    let mut counts = StreamCounts {
+        fresh: 0,
+        spoiled: 0,
+        invalid: 0,
+        per_range: Vec::new(),
+    };
+
+    // Raw bytes, so a line that isn't UTF-8 counts as invalid instead of ending the stream
+    let mut buf = Vec::new();
+    let mut line_no = 0;
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf).expect("Failed to read ID stream") == 0 {
+            break;
+        }
+        line_no += 1;
+        let parsed = std::str::from_utf8(&buf).map(|line| line.trim());
+        if parsed == Ok("") {
+            continue;
+        }
+        let Some(id) = parsed.ok().and_then(|line| line.parse::<u64>().ok()) else {
+            counts.invalid += 1;
+            if counts.invalid <= MAX_WARNINGS {
+                let line = String::from_utf8_lossy(&buf);
+                eprintln!("Warning: line {}: invalid ID \"{}\"", line_no, line.trim());
+            }
+            continue;
+        };
+
+        if fresh_set.contains(id) {
+            counts.fresh += 1;
+            // A fresh ID is always at or after the first boundary
+            segment_hits[index.segment(id).unwrap()] += 1;
         } else {
-            // Non-overlapping, add new range
-            merged.push((start, end));
+            counts.spoiled += 1;
+        }
+    }
+
+    if counts.invalid > MAX_WARNINGS {
+        eprintln!("Warning: {} more invalid lines not shown", counts.invalid - MAX_WARNINGS);
+    }
+
+    // Each range covers a contiguous run of segments, so prefix sums give its total
+    let mut prefix = vec![0u64; segment_hits.len() + 1];
+    for (i, hits) in segment_hits.iter().enumerate() {
+        prefix[i + 1] = prefix[i] + hits;
+    }
+    counts.per_range = ranges
+        .iter()
+        .map(|&(start, end)| {
+            let first = index.segment(start).unwrap();
+            let last = index.segment(end).unwrap();
+            prefix[last + 1] - prefix[first]
+        })
+        .collect();
+
+    counts
+}
+
+fn print_stream_counts(ranges: &[(u64, u64)], counts: &StreamCounts) {
+    for (&(start, end), hits) in ranges.iter().zip(&counts.per_range) {
+        if *hits > 0 {
+            println!("{}-{}: {}", start, end, hits);
         }
     }
+    println!("Fresh: {}", counts.fresh);
+    println!("Spoiled: {}", counts.spoiled);
+    if counts.invalid > 0 {
+        println!("Invalid: {}", counts.invalid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_ids() {
+        let ranges = [(3, 5), (10, 14), (16, 20), (12, 18)];
+        let ids = "1\n5\n8\n11\n\n17\n32\nbogus\n";
+        let counts = stream_ids(&ranges, ids.as_bytes());
+        assert_eq!((counts.fresh, counts.spoiled, counts.invalid), (3, 3, 1));
+        // 17 sits in both of the overlapping ranges
+        assert_eq!(counts.per_range, vec![1, 1, 1, 1]);
 
-    // Count total IDs in merged ranges
-    merged.iter().map(|&(start, end)| end - start + 1).sum()
+        let edge = stream_ids(&[(u64::MAX - 1, u64::MAX)], "18446744073709551615".as_bytes());
+        assert_eq!(edge.per_range, vec![1]);
+
+        // A line that isn't UTF-8 is skipped, not fatal
+        let bytes = stream_ids(&ranges, &b"4\n\xff\xfe\n17\n"[..]);
+        assert_eq!((bytes.fresh, bytes.spoiled, bytes.invalid), (2, 0, 1));
+    }
+
+    #[test]
+    fn test_parse_sections() {
+        let inventory = parse_input("\r\n3-5\r\n10-14 \r\n\r\n\r\n\r\n1\r\n5\r\n\r\n").unwrap();
+        assert_eq!(inventory.ranges, vec![(3, 5), (10, 14)]);
+        assert_eq!(inventory.ids, Some(vec![1, 5]));
+
+        let no_ids = parse_input("3-5\n10-14\n").unwrap();
+        assert_eq!(no_ids.ids, None);
+
+        let error = |input: &str| parse_input(input).err().map(|e| e.line);
+        assert_eq!(error("3-5\n14-10\n\n1"), Some(2));
+        assert_eq!(error("3-5\n7\n\n1"), Some(2));
+        assert_eq!(error("3-5\n\n1\nx\n"), Some(4));
+        assert_eq!(error("3-5\n\n1\n\n2"), Some(5));
+    }
+
+    #[test]
+    fn test_report_pieces() {
+        let ranges = [(10, 20), (12, 18), (3, 5), (10, 20), (30, 40), (1, 2)];
+        assert_eq!(redundant_ranges(&ranges), vec![(1, 0), (3, 0)]);
+
+        let fresh: IntervalSet<u64> = ranges.iter().copied().collect();
+        assert_eq!(fresh.interval_count(), 3);
+        let misses = near_misses(&fresh, &[0, 8, 15, 23, 25, 44, 100], 4);
+        assert_eq!(
+            misses,
+            vec![(0, 1, (1, 5)), (8, 2, (10, 20)), (23, 3, (10, 20)), (44, 4, (30, 40))]
+        );
+    }
 }
@@ -1,30 +1,238 @@
+use crate::bigint::BigUint;
+use crate::cli;
+use std::fmt;
 use std::fs;
 
-pub fn solve() {
-    let input = fs::read_to_string("inputs/day06.txt").expect("Failed to read input file");
+pub fn run(args: &[String]) {
+    let mode = cli::mode(6, args, &["--orders"], &[]);
+    match mode {
+        None => solve(),
+        Some("validate") => {
+            let grid = parse_grid(&read_input());
+            let issues = validate(&grid, &find_problem_ranges(&grid));
+            for issue in &issues {
+                println!("{}", issue);
+            }
+            if issues.is_empty() {
+                println!("No issues found");
+            } else {
+                println!("{} issue(s) found", issues.len());
+                std::process::exit(1);
+            }
+        }
+        Some("render") => {
+            let grid = parse_grid(&read_input());
+            for (start, end) in find_problem_ranges(&grid) {
+                println!("{}", render_problem(&grid, start, end));
+            }
+        }
+        Some("compare") => {
+            let orders: Vec<ReadingOrder> = match cli::option_value(args, "--orders") {
+                None => ReadingOrder::all(),
+                Some(list) => list
+                    .split(',')
+                    .map(|name| {
+                        name.parse().unwrap_or_else(|e| {
+                            eprintln!("{}", e);
+                            std::process::exit(1);
+                        })
+                    })
+                    .collect(),
+            };
+            let grid = parse_grid(&read_input());
+            compare_orders(&grid, &find_problem_ranges(&grid), &orders);
+        }
+        Some(mode) => cli::unknown_mode(6, mode),
+    }
+}
+
+fn read_input() -> String {
+    fs::read_to_string("inputs/day06.txt").expect("Failed to read input file")
+}
 
-    let grid = parse_grid(&input);
+pub fn solve() {
+    let grid = parse_grid(&read_input());
     let problem_ranges = find_problem_ranges(&grid);
 
     // Part 1: Read numbers horizontally (row by row)
-    let total1: u64 = problem_ranges
-        .iter()
-        .map(|&(start, end)| {
-            let (nums, op) = extract_problem_horizontal(&grid, start, end);
-            calculate(&nums, op)
-        })
-        .sum();
+    let total1 = solve_worksheet(&grid, &problem_ranges, ReadingOrder::ROWS);
     println!("Part 1: {}", total1);
 
     // Part 2: Read numbers vertically (column by column, right to left)
-    let total2: u64 = problem_ranges
+    let total2 = solve_worksheet(&grid, &problem_ranges, ReadingOrder::COLUMNS_RTL);
+    println!("Part 2: {}", total2);
+}
+
+fn solve_worksheet(
+    grid: &[Vec<char>],
+    problem_ranges: &[(usize, usize)],
+    order: ReadingOrder,
+) -> BigUint {
+    let result: Result<BigUint, WorksheetError> = problem_ranges
         .iter()
-        .map(|&(start, end)| {
-            let (nums, op) = extract_problem_vertical(&grid, start, end);
-            calculate(&nums, op)
-        })
+        .map(|&(start, end)| extract_problem(grid, start, end, order)?.evaluate())
         .sum();
-    println!("Part 2: {}", total2);
+    result.unwrap_or_else(|e| {
+        eprintln!("inputs/day06.txt: {}", e);
+        std::process::exit(1);
+    })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Add,
+    Multiply,
+    Subtract,
+    Divide,
+    Remainder,
+    Min,
+    Max,
+    Power,
+}
+
+impl Operator {
+    fn from_glyph(c: char) -> Option<Self> {
+        match c {
+            '+' => Some(Operator::Add),
+            '*' => Some(Operator::Multiply),
+            '-' => Some(Operator::Subtract),
+            '/' => Some(Operator::Divide),
+            '%' => Some(Operator::Remainder),
+            '<' => Some(Operator::Min),
+            '>' => Some(Operator::Max),
+            '^' => Some(Operator::Power),
+            _ => None,
+        }
+    }
+
+    fn glyph(self) -> char {
+        match self {
+            Operator::Add => '+',
+            Operator::Multiply => '*',
+            Operator::Subtract => '-',
+            Operator::Divide => '/',
+            Operator::Remainder => '%',
+            Operator::Min => '<',
+            Operator::Max => '>',
+            Operator::Power => '^',
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Problem {
+    operands: Vec<u64>,
+    operator: Operator,
+}
+
+#[derive(Debug, PartialEq)]
+enum WorksheetError {
+    UnknownOperator { column: usize, glyph: char },
+    MissingOperator { start_col: usize, end_col: usize },
+    InvalidNumber { row: usize, column: usize, text: String },
+    NoOperands(Operator),
+    DivisionByZero,
+    Negative,
+    TooLarge,
+}
+
+impl fmt::Display for WorksheetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WorksheetError::UnknownOperator { column, glyph } => {
+                write!(f, "unknown operator '{}' in column {}", glyph, column + 1)
+            }
+            WorksheetError::MissingOperator { start_col, end_col } => {
+                write!(f, "no operator for the problem in columns {}-{}", start_col + 1, end_col)
+            }
+            WorksheetError::InvalidNumber { row, column, text } => {
+                write!(f, "invalid number \"{}\" at row {}, column {}", text, row + 1, column + 1)
+            }
+            WorksheetError::NoOperands(op) => write!(f, "{:?} problem has no numbers", op),
+            WorksheetError::DivisionByZero => write!(f, "division by zero"),
+            WorksheetError::Negative => write!(f, "subtraction result is negative"),
+            WorksheetError::TooLarge => write!(f, "power is too large to evaluate"),
+        }
+    }
+}
+
+// Powers beyond this many decimal digits are refused rather than computed
+const MAX_POWER_DIGITS: f64 = 100_000.0;
+
+impl Problem {
+    fn evaluate(&self) -> Result<BigUint, WorksheetError> {
+        let nums = &self.operands;
+        let (&first, rest) = match nums.split_first() {
+            Some(split) => split,
+            // An empty sum or product is still well defined
+            None => match self.operator {
+                Operator::Add => return Ok(BigUint::zero()),
+                Operator::Multiply => return Ok(BigUint::from(1)),
+                op => return Err(WorksheetError::NoOperands(op)),
+            },
+        };
+
+        // Sums, products and powers try checked u64 arithmetic first and only fall back to
+        // big integers when that overflows. Everything else can't exceed its largest operand.
+        // Operands combine left to right, except powers which stack right to left.
+        match self.operator {
+            Operator::Add => Ok(match nums.iter().try_fold(0u64, |acc, &n| acc.checked_add(n)) {
+                Some(sum) => BigUint::from(sum),
+                None => nums.iter().map(|&n| BigUint::from(n)).sum(),
+            }),
+            Operator::Multiply => Ok(match nums.iter().try_fold(1u64, |a, &n| a.checked_mul(n)) {
+                Some(product) => BigUint::from(product),
+                None => nums.iter().fold(BigUint::from(1), |mut acc, &n| {
+                    acc *= &BigUint::from(n);
+                    acc
+                }),
+            }),
+            Operator::Subtract => rest
+                .iter()
+                .try_fold(first, |acc, &n| acc.checked_sub(n))
+                .map(BigUint::from)
+                .ok_or(WorksheetError::Negative),
+            Operator::Divide | Operator::Remainder => rest
+                .iter()
+                .try_fold(first, |acc, &n| {
+                    if n == 0 {
+                        Err(WorksheetError::DivisionByZero)
+                    } else if self.operator == Operator::Divide {
+                        Ok(acc / n)
+                    } else {
+                        Ok(acc % n)
+                    }
+                })
+                .map(BigUint::from),
+            Operator::Min => Ok(BigUint::from(*nums.iter().min().unwrap())),
+            Operator::Max => Ok(BigUint::from(*nums.iter().max().unwrap())),
+            Operator::Power => power_tower(first, rest),
+        }
+    }
+}
+
+fn power_tower(base: u64, exponents: &[u64]) -> Result<BigUint, WorksheetError> {
+    // base ^ (e1 ^ (e2 ^ ...))
+    let Some((&next, rest)) = exponents.split_first() else {
+        return Ok(BigUint::from(base));
+    };
+    let exponent = power_tower(next, rest)?;
+
+    if exponent == BigUint::zero() {
+        return Ok(BigUint::from(1));
+    }
+    if base <= 1 {
+        return Ok(BigUint::from(base));
+    }
+    let exponent = exponent.to_u64().ok_or(WorksheetError::TooLarge)?;
+
+    if let Some(power) = u32::try_from(exponent).ok().and_then(|e| base.checked_pow(e)) {
+        return Ok(BigUint::from(power));
+    }
+    if exponent as f64 * (base as f64).log10() > MAX_POWER_DIGITS {
+        return Err(WorksheetError::TooLarge);
+    }
+    Ok(BigUint::from(base).pow(exponent))
 }
 
 fn parse_grid(input: &str) -> Vec<Vec<char>> {
@@ -79,64 +287,407 @@ fn find_problem_ranges(grid: &[Vec<char>]) -> Vec<(usize, usize)> {
     ranges
 }
 
-fn get_operator(grid: &[Vec<char>], start_col: usize, end_col: usize) -> char {
+fn get_operator(
+    grid: &[Vec<char>],
+    start_col: usize,
+    end_col: usize,
+) -> Result<Operator, WorksheetError> {
     let operator_row = &grid[grid.len() - 1];
-    for col in start_col..end_col {
-        let c = operator_row[col];
-        if c == '+' || c == '*' {
-            return c;
+    for (col, &c) in operator_row.iter().enumerate().take(end_col).skip(start_col) {
+        if c == ' ' {
+            continue;
         }
+        return Operator::from_glyph(c)
+            .ok_or(WorksheetError::UnknownOperator { column: col, glyph: c });
     }
-    '+'
+    Err(WorksheetError::MissingOperator { start_col, end_col })
 }
 
-fn extract_problem_horizontal(grid: &[Vec<char>], start_col: usize, end_col: usize) -> (Vec<u64>, char) {
-    let operator = get_operator(grid, start_col, end_col);
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Axis {
+    Rows,
+    Columns,
+}
 
-    // Extract numbers from rows above the operator row (read horizontally)
-    let mut numbers = vec![];
-    for row in &grid[..grid.len() - 1] {
-        let segment: String = row[start_col..end_col].iter().collect();
-        let trimmed = segment.trim();
-        if !trimmed.is_empty() {
-            if let Ok(num) = trimmed.parse::<u64>() {
-                numbers.push(num);
+// Rows are read top-down and columns left-to-right unless `reverse` is set; each number's
+// digits are read left-to-right (rows) or top-down (columns) unless `reverse_digits` is set
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ReadingOrder {
+    axis: Axis,
+    reverse: bool,
+    reverse_digits: bool,
+}
+
+impl ReadingOrder {
+    // Part 1 and part 2 of the puzzle
+    const ROWS: ReadingOrder = ReadingOrder {
+        axis: Axis::Rows,
+        reverse: false,
+        reverse_digits: false,
+    };
+    const COLUMNS_RTL: ReadingOrder = ReadingOrder {
+        axis: Axis::Columns,
+        reverse: true,
+        reverse_digits: false,
+    };
+
+    fn all() -> Vec<ReadingOrder> {
+        let mut orders = Vec::new();
+        for axis in [Axis::Rows, Axis::Columns] {
+            for reverse in [false, true] {
+                for reverse_digits in [false, true] {
+                    orders.push(ReadingOrder { axis, reverse, reverse_digits });
+                }
             }
         }
+        orders
+    }
+}
+
+impl fmt::Display for ReadingOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match (self.axis, self.reverse) {
+            (Axis::Rows, false) => "rows-down",
+            (Axis::Rows, true) => "rows-up",
+            (Axis::Columns, false) => "cols-ltr",
+            (Axis::Columns, true) => "cols-rtl",
+        };
+        write!(f, "{}{}", name, if self.reverse_digits { "-rev" } else { "" })
     }
+}
+
+impl std::str::FromStr for ReadingOrder {
+    type Err = String;
 
-    (numbers, operator)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ReadingOrder::all()
+            .into_iter()
+            .find(|order| order.to_string() == s)
+            .ok_or_else(|| format!("unknown reading order: {}", s))
+    }
 }
 
-fn extract_problem_vertical(grid: &[Vec<char>], start_col: usize, end_col: usize) -> (Vec<u64>, char) {
-    let operator = get_operator(grid, start_col, end_col);
+fn extract_problem(
+    grid: &[Vec<char>],
+    start_col: usize,
+    end_col: usize,
+    order: ReadingOrder,
+) -> Result<Problem, WorksheetError> {
+    let operator = get_operator(grid, start_col, end_col)?;
+    let number_rows = &grid[..grid.len() - 1];
+
+    // Each line is the run of cells one number is read from, as (row, col, char)
+    let mut lines: Vec<Vec<(usize, usize, char)>> = match order.axis {
+        Axis::Rows => number_rows
+            .iter()
+            .enumerate()
+            .map(|(r, row)| (start_col..end_col).map(|c| (r, c, row[c])).collect())
+            .collect(),
+        Axis::Columns => (start_col..end_col)
+            .map(|c| number_rows.iter().enumerate().map(|(r, row)| (r, c, row[c])).collect())
+            .collect(),
+    };
+    if order.reverse {
+        lines.reverse();
+    }
 
-    // Extract numbers by reading columns right-to-left
-    // Each column forms a number (top digit = most significant)
     let mut numbers = vec![];
+    for line in lines {
+        // Rows hold one number padded with spaces; columns may skip blank cells between digits
+        let cells: Vec<(usize, usize, char)> = match order.axis {
+            Axis::Rows => {
+                let first = line.iter().position(|&(_, _, c)| c != ' ');
+                let last = line.iter().rposition(|&(_, _, c)| c != ' ');
+                match (first, last) {
+                    (Some(first), Some(last)) => line[first..=last].to_vec(),
+                    _ => Vec::new(),
+                }
+            }
+            Axis::Columns => line.into_iter().filter(|&(_, _, c)| c != ' ').collect(),
+        };
+        let Some(&(row, column, _)) = cells.first() else {
+            continue;
+        };
+        let text: String = cells.iter().map(|&(_, _, c)| c).collect();
+        let invalid = || WorksheetError::InvalidNumber { row, column, text: text.clone() };
+        if !text.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let digits: String = if order.reverse_digits {
+            text.chars().rev().collect()
+        } else {
+            text.clone()
+        };
+        numbers.push(digits.parse::<u64>().map_err(|_| invalid())?);
+    }
+
+    Ok(Problem { operands: numbers, operator })
+}
+
+fn compare_orders(grid: &[Vec<char>], problem_ranges: &[(usize, usize)], orders: &[ReadingOrder]) {
+    // One row per problem and a final total row, one column per reading order
+    let mut table: Vec<Vec<String>> = vec![];
+    let mut header = vec!["problem".to_string()];
+    header.extend(orders.iter().map(|order| order.to_string()));
+    table.push(header);
 
-    for col in (start_col..end_col).rev() {
-        let mut digits = String::new();
-        for row in &grid[..grid.len() - 1] {
-            let c = row[col];
-            if c.is_ascii_digit() {
-                digits.push(c);
+    let mut totals: Vec<Option<BigUint>> = vec![Some(BigUint::zero()); orders.len()];
+    for &(start, end) in problem_ranges {
+        let mut row = vec![format!("{}-{}", start + 1, end)];
+        for (order, total) in orders.iter().zip(totals.iter_mut()) {
+            match extract_problem(grid, start, end, *order).and_then(|p| p.evaluate()) {
+                Ok(value) => {
+                    row.push(value.to_string());
+                    if let Some(total) = total {
+                        *total += &value;
+                    }
+                }
+                Err(_) => {
+                    row.push("error".to_string());
+                    *total = None;
+                }
             }
         }
-        if !digits.is_empty() {
-            if let Ok(num) = digits.parse::<u64>() {
-                numbers.push(num);
+        table.push(row);
+    }
+    let mut total_row = vec!["total".to_string()];
+    total_row.extend(totals.iter().map(|total| match total {
+        Some(total) => total.to_string(),
+        None => "error".to_string(),
+    }));
+    table.push(total_row);
+
+    let widths: Vec<usize> = (0..table[0].len())
+        .map(|i| table.iter().map(|row| row[i].len()).max().unwrap_or(0))
+        .collect();
+    for row in &table {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{:>width$}", cell, width = width))
+            .collect();
+        println!("{}", cells.join("  "));
+    }
+}
+
+struct Issue {
+    row: usize,
+    column: usize,
+    message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "row {}, column {}: {}", self.row + 1, self.column + 1, self.message)
+    }
+}
+
+fn validate(grid: &[Vec<char>], problem_ranges: &[(usize, usize)]) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let Some((operator_row, number_rows)) = grid.split_last() else {
+        return issues;
+    };
+    let op_row = number_rows.len();
+
+    for &(start, end) in problem_ranges {
+        // Number rows: only digits and spaces, and at most one number per row
+        for (r, row) in number_rows.iter().enumerate() {
+            let mut runs = 0;
+            let mut in_run = false;
+            for (col, &c) in row.iter().enumerate().take(end).skip(start) {
+                if c.is_ascii_digit() {
+                    if !in_run {
+                        runs += 1;
+                        if runs == 2 {
+                            issues.push(Issue {
+                                row: r,
+                                column: col,
+                                message: "digits are split into more than one number".to_string(),
+                            });
+                        }
+                    }
+                    in_run = true;
+                } else {
+                    in_run = false;
+                    if c != ' ' {
+                        issues.push(Issue {
+                            row: r,
+                            column: col,
+                            message: format!("non-numeric character '{}'", c),
+                        });
+                    }
+                }
             }
         }
+
+        // Operator row: exactly one known operator, in the problem's first column
+        let glyphs: Vec<(usize, char)> = (start..end)
+            .map(|col| (col, operator_row[col]))
+            .filter(|&(_, c)| c != ' ')
+            .collect();
+        if glyphs.is_empty() {
+            issues.push(Issue {
+                row: op_row,
+                column: start,
+                message: format!("problem in columns {}-{} has no operator", start + 1, end),
+            });
+        }
+        for (i, &(col, c)) in glyphs.iter().enumerate() {
+            let message = if Operator::from_glyph(c).is_none() {
+                format!("unknown operator '{}'", c)
+            } else if i > 0 {
+                format!("extra operator '{}'", c)
+            } else if col != start {
+                format!("operator '{}' is not aligned with the problem's first column", c)
+            } else {
+                continue;
+            };
+            issues.push(Issue { row: op_row, column: col, message });
+        }
+    }
+
+    issues
+}
+
+fn equation(problem: Result<Problem, WorksheetError>) -> String {
+    let problem = match problem {
+        Ok(problem) => problem,
+        Err(e) => return format!("error: {}", e),
+    };
+    let operands: Vec<String> = problem.operands.iter().map(|n| n.to_string()).collect();
+    let lhs = operands.join(&format!(" {} ", problem.operator.glyph()));
+    match problem.evaluate() {
+        Ok(result) => format!("{} = {}", lhs, result),
+        Err(e) => format!("{} = error: {}", lhs, e),
     }
+}
+
+fn render_problem(grid: &[Vec<char>], start: usize, end: usize) -> String {
+    let mut lines: Vec<String> = grid.iter().map(|row| row[start..end].iter().collect()).collect();
+    let divider = lines.len();
+    let read = |order| equation(extract_problem(grid, start, end, order));
+    lines.push(format!("rows:    {}", read(ReadingOrder::ROWS)));
+    lines.push(format!("columns: {}", read(ReadingOrder::COLUMNS_RTL)));
 
-    (numbers, operator)
+    let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let rule = "─".repeat(width + 2);
+    let mut out = format!("┌{}┐\n", rule);
+    for (i, line) in lines.iter().enumerate() {
+        if i == divider {
+            out.push_str(&format!("├{}┤\n", rule));
+        }
+        let pad = width - line.chars().count();
+        out.push_str(&format!("│ {}{} │\n", line, " ".repeat(pad)));
+    }
+    out.push_str(&format!("└{}┘", rule));
+    out
 }
 
-fn calculate(nums: &[u64], op: char) -> u64 {
-    match op {
-        '+' => nums.iter().sum(),
-        '*' => nums.iter().product(),
-        _ => 0,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "123 328  51 64 \n 45 64  387 23 \n  6 98  215 314\n*   +   *   +  \n";
+
+    #[test]
+    fn test_example() {
+        let grid = parse_grid(EXAMPLE);
+        let ranges = find_problem_ranges(&grid);
+        let part1 = solve_worksheet(&grid, &ranges, ReadingOrder::ROWS);
+        assert_eq!(part1, BigUint::from(4277556));
+        let part2 = solve_worksheet(&grid, &ranges, ReadingOrder::COLUMNS_RTL);
+        assert_eq!(part2, BigUint::from(3263827));
+    }
+
+    #[test]
+    fn test_operators() {
+        let eval = |glyph, operands: &[u64]| {
+            let operator = Operator::from_glyph(glyph).unwrap();
+            let problem = Problem { operands: operands.to_vec(), operator };
+            problem.evaluate().map(|n| n.to_string())
+        };
+        let ok = |n: &str| Ok(n.to_string());
+        assert_eq!(eval('-', &[100, 30, 5]), ok("65"));
+        assert_eq!(eval('-', &[1, 2]), Err(WorksheetError::Negative));
+        assert_eq!(eval('/', &[100, 5, 3]), ok("6"));
+        assert_eq!(eval('%', &[100, 7]), ok("2"));
+        assert_eq!(eval('/', &[1, 0]), Err(WorksheetError::DivisionByZero));
+        assert_eq!(eval('<', &[4, 2, 9]), ok("2"));
+        assert_eq!(eval('>', &[4, 2, 9]), ok("9"));
+        assert_eq!(eval('^', &[2, 3, 2]), ok("512"));
+        assert_eq!(eval('^', &[2, 64]), ok("18446744073709551616"));
+        assert_eq!(eval('^', &[1, 5, 30]), ok("1"));
+        assert_eq!(eval('^', &[0, 0]), ok("1"));
+        assert_eq!(eval('^', &[10, 10, 10]), Err(WorksheetError::TooLarge));
+
+        // Wide products and sums overflow u64 and fall back to big integers
+        assert_eq!(eval('*', &[u64::MAX, u64::MAX]), ok("340282366920938463426481119284349108225"));
+        assert_eq!(eval('+', &[u64::MAX, 1]), ok("18446744073709551616"));
+        assert_eq!(eval('*', &[]), ok("1"));
+
+        let grid = parse_grid("12 3\n4  5\n+  ?\n");
+        let ranges = find_problem_ranges(&grid);
+        assert_eq!(
+            extract_problem(&grid, ranges[1].0, ranges[1].1, ReadingOrder::ROWS),
+            Err(WorksheetError::UnknownOperator { column: 3, glyph: '?' })
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let grid = parse_grid("12 3  1x\n 45   2 \n+ *   ? \n");
+        let ranges = find_problem_ranges(&grid);
+        assert_eq!(ranges, vec![(0, 4), (6, 8)]);
+        let issues: Vec<String> = validate(&grid, &ranges).iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            issues,
+            vec![
+                "row 1, column 4: digits are split into more than one number",
+                "row 3, column 3: extra operator '*'",
+                "row 1, column 8: non-numeric character 'x'",
+                "row 3, column 7: unknown operator '?'",
+            ]
+        );
+        let example = parse_grid(EXAMPLE);
+        assert!(validate(&example, &find_problem_ranges(&example)).is_empty());
+
+        let junk = parse_grid(" 2\n1x\n+ \n");
+        assert_eq!(
+            extract_problem(&junk, 0, 2, ReadingOrder::ROWS).unwrap_err().to_string(),
+            "invalid number \"1x\" at row 2, column 1"
+        );
+    }
+
+    #[test]
+    fn test_render_problem() {
+        let grid = parse_grid(EXAMPLE);
+        let rendered = render_problem(&grid, 0, 3);
+        assert!(rendered.contains("│ rows:    123 * 45 * 6 = 33210 │"));
+        assert!(rendered.contains("│ columns: 356 * 24 * 1 = 8544  │"));
+        assert!(rendered.starts_with("┌────"));
+        assert_eq!(rendered.lines().count(), 9);
+    }
+
+    #[test]
+    fn test_reading_orders() {
+        let grid = parse_grid("12 \n 34\n-  \n");
+        let read = |name: &str| {
+            let order: ReadingOrder = name.parse().unwrap();
+            assert_eq!(order.to_string(), name);
+            extract_problem(&grid, 0, 3, order).unwrap().operands
+        };
+        assert_eq!(read("rows-down"), vec![12, 34]);
+        assert_eq!(read("rows-up"), vec![34, 12]);
+        assert_eq!(read("rows-down-rev"), vec![21, 43]);
+        assert_eq!(read("cols-ltr"), vec![1, 23, 4]);
+        assert_eq!(read("cols-rtl"), vec![4, 23, 1]);
+        assert_eq!(read("cols-rtl-rev"), vec![4, 32, 1]);
+        assert_eq!(ReadingOrder::all().len(), 8);
+        assert!("diagonal".parse::<ReadingOrder>().is_err());
+
+        let example = parse_grid(EXAMPLE);
+        let ranges = find_problem_ranges(&example);
+        let up = ReadingOrder { reverse: true, ..ReadingOrder::ROWS };
+        assert_eq!(solve_worksheet(&example, &ranges, up), BigUint::from(4277556));
     }
 }
@@ -1,143 +1,552 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use crate::bigint::BigUint;
+use crate::cli;
+use crate::grid::{Grid, Pos};
+use std::fmt;
 use std::fs;
 
-pub fn solve() {
+pub fn run(args: &[String]) {
+    let mode = cli::mode(7, args, &["--modulus", "--top", "--ppm", "--scale"], &[]);
+    // Optionally report timeline counts modulo this value (0 = exact)
+    let modulus = Some(cli::parse_option(args, "--modulus", 0u64)).filter(|&m| m > 0);
+    match mode {
+        None => solve(modulus),
+        Some("stats") => {
+            let summary = trace_or_exit(&load_grid(), modulus);
+            print_stats(&summary, cli::parse_option(args, "--top", 10));
+        }
+        Some("render") => {
+            let grid = load_grid();
+            print!("{}", render_paths(&grid, &trace_or_exit(&grid, modulus)));
+            println!("Legend: | beam, * splitter hit, ^ splitter never hit");
+        }
+        Some("heat") => {
+            // A log scale only makes sense on exact counts, so the modulus is ignored here
+            let summary = trace_or_exit(&load_grid(), None);
+            match cli::option_value(args, "--ppm") {
+                Some(path) => {
+                    let scale = cli::parse_option(args, "--scale", 4);
+                    let image = heat_map(&summary.occupancy).to_ppm(scale, |&t| heat_color(t));
+                    fs::write(path, image)
+                        .unwrap_or_else(|e| panic!("Failed to write {}: {}", path, e));
+                    println!("Wrote {}", path);
+                }
+                None => print!("{}", heat_map(&summary.occupancy).render(|&t| heat_glyph(t))),
+            }
+        }
+        Some(mode) => cli::unknown_mode(7, mode),
+    }
+}
+
+fn load_grid() -> Grid<char> {
     let input = fs::read_to_string("inputs/day07.txt").expect("Failed to read input file");
+    Grid::parse(input.as_str(), |c| c)
+}
 
-    let grid: Vec<Vec<char>> = input.lines().map(|l| l.chars().collect()).collect();
+fn solve(modulus: Option<u64>) {
+    let summary = trace_or_exit(&load_grid(), modulus);
 
-    // Part 1: Count how many times the beam is split
-    let splits = count_splits(&grid);
-    println!("Part 1: {}", splits);
+    // Part 1: Count how many splitters the beam reaches
+    println!("Part 1: {}", summary.splits);
 
     // Part 2: Count total timelines (each split doubles timelines on that path)
-    let timelines = count_timelines(&grid);
-    println!("Part 2: {}", timelines);
+    println!("Part 2: {}", summary.timelines);
+}
+
+fn trace_or_exit(grid: &Grid<char>, modulus: Option<u64>) -> BeamSummary {
+    trace_beams(grid, modulus).unwrap_or_else(|e| {
+        eprintln!("inputs/day07.txt: {}", e);
+        std::process::exit(1);
+    })
+}
+
+// Timeline count that switches to arbitrary precision once it no longer fits in a u64.
+// Big values are always above u64::MAX, so the derived ordering is numeric.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Count {
+    Small(u64),
+    Big(BigUint),
 }
 
-fn count_splits(grid: &[Vec<char>]) -> usize {
-    if grid.is_empty() {
-        return 0;
+impl Count {
+    fn zero() -> Self {
+        Count::Small(0)
     }
 
-    let rows = grid.len();
-    let cols = grid[0].len();
+    fn is_zero(&self) -> bool {
+        *self == Count::Small(0)
+    }
 
-    // Find starting position 'S'
-    let mut start_row = 0;
-    let mut start_col = 0;
-    for (r, row) in grid.iter().enumerate() {
-        for (c, &ch) in row.iter().enumerate() {
-            if ch == 'S' {
-                start_row = r;
-                start_col = c;
+    // With a modulus both sides are already reduced, so the count stays small
+    fn add(&mut self, other: &Count, modulus: Option<u64>) {
+        match (&mut *self, other, modulus) {
+            (Count::Small(a), Count::Small(b), Some(m)) => {
+                *a = ((*a as u128 + *b as u128) % m as u128) as u64;
+            }
+            (Count::Small(a), Count::Small(b), None) => {
+                if let Some(sum) = a.checked_add(*b) {
+                    *a = sum;
+                } else {
+                    let mut big = BigUint::from(*a);
+                    big += &BigUint::from(*b);
+                    *self = Count::Big(big);
+                }
+            }
+            (Count::Small(a), Count::Big(b), _) => {
+                let mut big = BigUint::from(*a);
+                big += b;
+                *self = Count::Big(big);
             }
+            (Count::Big(a), Count::Small(b), _) => *a += &BigUint::from(*b),
+            (Count::Big(a), Count::Big(b), _) => *a += b,
         }
     }
 
-    // BFS to simulate beams - each beam has (row, col) and moves downward
-    // When splitting, it creates beams at col-1 and col+1
-    let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
-    let mut visited: HashSet<(usize, usize)> = HashSet::new();
+    // Approximate base-2 logarithm, for scaling heat maps
+    fn log2(&self) -> f64 {
+        match self {
+            Count::Small(n) => (*n as f64).log2(),
+            Count::Big(n) => {
+                let digits = n.to_string();
+                let lead: f64 = digits[..15].parse().unwrap();
+                lead.log2() + (digits.len() - 15) as f64 * 10f64.log2()
+            }
+        }
+    }
+}
 
-    // Beam starts at S and moves downward
-    queue.push_back((start_row, start_col));
+impl fmt::Display for Count {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Count::Small(n) => write!(f, "{}", n),
+            Count::Big(n) => write!(f, "{}", n),
+        }
+    }
+}
 
-    let mut split_count = 0;
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Dir {
+    Up,
+    Down,
+    Left,
+    Right,
+}
 
-    while let Some((row, col)) = queue.pop_front() {
-        // Check if this position was already visited
-        if visited.contains(&(row, col)) {
-            continue;
+impl Dir {
+    fn delta(self) -> (isize, isize) {
+        match self {
+            Dir::Up => (-1, 0),
+            Dir::Down => (1, 0),
+            Dir::Left => (0, -1),
+            Dir::Right => (0, 1),
         }
-        visited.insert((row, col));
+    }
+
+    fn is_vertical(self) -> bool {
+        matches!(self, Dir::Up | Dir::Down)
+    }
+}
+
+// A beam occupying a cell and about to move in a direction
+type Beam = (Pos, Dir);
 
-        // Move downward
-        if row + 1 >= rows {
-            continue;
+enum Target {
+    Beam(Beam),
+    // Left the grid through the bottom edge in this column
+    Exit(usize),
+    // Left the grid through any other edge
+    Escaped,
+    Absorbed,
+}
+
+// What happens to a beam as it moves one cell. Cells:
+//   `.` `S` and anything unknown: pass straight through
+//   `^`: split into the two cells beside it (across the direction of travel), which keep
+//        moving the same way without interacting with their own cells
+//   `#`: absorb the beam
+//   `<` `>`: send the beam left or right from that cell
+//   `/` `\`: mirrors that turn the beam a quarter turn
+// Returns the splitter that was hit, if any, and where the beam goes next.
+fn step(grid: &Grid<char>, (pos, dir): Beam) -> (Option<Pos>, Vec<Target>) {
+    let Some(next) = grid.offset(pos, dir.delta()) else {
+        let target = if dir == Dir::Down { Target::Exit(pos.1) } else { Target::Escaped };
+        return (None, vec![target]);
+    };
+    let turn = |dir| vec![Target::Beam((next, dir))];
+    match grid[next] {
+        '^' => {
+            let sides = if dir.is_vertical() { [(0, -1), (0, 1)] } else { [(-1, 0), (1, 0)] };
+            let targets = sides
+                .iter()
+                .map(|&side| match grid.offset(next, side) {
+                    Some(beside) => Target::Beam((beside, dir)),
+                    None => Target::Escaped,
+                })
+                .collect();
+            (Some(next), targets)
+        }
+        '#' => (None, vec![Target::Absorbed]),
+        '<' => (None, turn(Dir::Left)),
+        '>' => (None, turn(Dir::Right)),
+        '/' => {
+            let dir = match dir {
+                Dir::Up => Dir::Right,
+                Dir::Down => Dir::Left,
+                Dir::Left => Dir::Down,
+                Dir::Right => Dir::Up,
+            };
+            (None, turn(dir))
         }
-        let next_row = row + 1;
-        let cell = grid[next_row][col];
+        '\\' => {
+            let dir = match dir {
+                Dir::Up => Dir::Left,
+                Dir::Down => Dir::Right,
+                Dir::Left => Dir::Up,
+                Dir::Right => Dir::Down,
+            };
+            (None, turn(dir))
+        }
+        _ => (None, turn(dir)),
+    }
+}
 
-        match cell {
-            '.' | 'S' => {
-                // Continue downward
-                queue.push_back((next_row, col));
-            }
-            '^' => {
-                // Splitter: count split, create left and right beams
-                split_count += 1;
-                if col > 0 {
-                    queue.push_back((next_row, col - 1));
-                }
-                if col + 1 < cols {
-                    queue.push_back((next_row, col + 1));
+#[derive(Debug, PartialEq)]
+struct LoopError {
+    pos: Pos,
+}
+
+impl fmt::Display for LoopError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (row, col) = self.pos;
+        write!(f, "beam loops forever through row {}, column {}", row + 1, col + 1)
+    }
+}
+
+struct BeamSummary {
+    // Number of distinct splitters reached by at least one timeline
+    splits: usize,
+    // Timelines leaving through the bottom edge
+    timelines: Count,
+    // Timelines arriving at each cell, nonzero only for reached splitters
+    hits: Grid<Count>,
+    // Splitters reached by at least one timeline; unlike hits, this survives a modulus
+    splitter_reached: Grid<bool>,
+    // Timelines passing through each cell, in any direction
+    occupancy: Grid<Count>,
+    // Cells any beam passes through, which like splitter_reached survives a modulus
+    visited: Grid<bool>,
+    // Timelines leaving the bottom row, per column
+    exits: Vec<Count>,
+    // Timelines leaving through the top or side edges
+    escaped: Count,
+    absorbed: Count,
+}
+
+// Counts are exact unless a modulus is given, in which case they are reduced modulo it
+fn trace_beams(grid: &Grid<char>, modulus: Option<u64>) -> Result<BeamSummary, LoopError> {
+    let cols = grid.width();
+    let dirs = [Dir::Up, Dir::Down, Dir::Left, Dir::Right];
+    let index = |((row, col), dir): Beam| (row * cols + col) * 4 + dir as usize;
+    let beam_at = |i: usize| -> Beam { ((i / 4 / cols, i / 4 % cols), dirs[i % 4]) };
+
+    // Every source emits one timeline downward
+    let sources: Vec<Beam> = grid
+        .iter()
+        .filter(|&(_, &c)| c == 'S')
+        .map(|(pos, _)| (pos, Dir::Down))
+        .collect();
+
+    // Find every reachable beam state and how many others lead into it
+    let states = grid.width() * grid.height() * 4;
+    let mut reached = vec![false; states];
+    let mut incoming = vec![0usize; states];
+    let mut stack: Vec<usize> = sources.iter().map(|&beam| index(beam)).collect();
+    for &i in &stack {
+        reached[i] = true;
+    }
+    while let Some(i) = stack.pop() {
+        for target in step(grid, beam_at(i)).1 {
+            if let Target::Beam(beam) = target {
+                let j = index(beam);
+                incoming[j] += 1;
+                if !reached[j] {
+                    reached[j] = true;
+                    stack.push(j);
                 }
             }
-            _ => {
-                // Unknown cell, continue downward
-                queue.push_back((next_row, col));
+        }
+    }
+
+    // Propagate timeline counts in topological order; anything left over lies on a loop
+    let one = Count::Small(modulus.map_or(1, |m| 1 % m));
+    let mut counts = vec![Count::zero(); states];
+    for &beam in &sources {
+        counts[index(beam)].add(&one, modulus);
+    }
+    let mut ready: Vec<usize> = (0..states).filter(|&i| reached[i] && incoming[i] == 0).collect();
+    let mut hits = Grid::new(cols, grid.height(), Count::zero());
+    let mut splitter_reached = Grid::new(cols, grid.height(), false);
+    let mut occupancy = Grid::new(cols, grid.height(), Count::zero());
+    let mut visited = Grid::new(cols, grid.height(), false);
+    let mut exits = vec![Count::zero(); cols];
+    let (mut escaped, mut absorbed) = (Count::zero(), Count::zero());
+    let mut splits = 0;
+    let mut processed = 0;
+
+    while let Some(i) = ready.pop() {
+        processed += 1;
+        let count = std::mem::replace(&mut counts[i], Count::zero());
+        occupancy[beam_at(i).0].add(&count, modulus);
+        visited[beam_at(i).0] = true;
+        let (splitter, targets) = step(grid, beam_at(i));
+        if let Some(splitter) = splitter {
+            // Under a modulus a reached splitter's hit count can still be zero
+            if !splitter_reached[splitter] {
+                splitter_reached[splitter] = true;
+                splits += 1;
+            }
+            hits[splitter].add(&count, modulus);
+        }
+        for target in targets {
+            match target {
+                Target::Beam(beam) => {
+                    let j = index(beam);
+                    counts[j].add(&count, modulus);
+                    incoming[j] -= 1;
+                    if incoming[j] == 0 {
+                        ready.push(j);
+                    }
+                }
+                Target::Exit(col) => exits[col].add(&count, modulus),
+                Target::Escaped => escaped.add(&count, modulus),
+                Target::Absorbed => absorbed.add(&count, modulus),
             }
         }
     }
 
-    split_count
+    if processed < reached.iter().filter(|&&r| r).count() {
+        let stuck = (0..states).find(|&i| reached[i] && incoming[i] > 0).unwrap();
+        return Err(LoopError { pos: beam_at(stuck).0 });
+    }
+
+    let mut timelines = Count::zero();
+    for exit in &exits {
+        timelines.add(exit, modulus);
+    }
+    Ok(BeamSummary {
+        splits,
+        timelines,
+        hits,
+        splitter_reached,
+        occupancy,
+        visited,
+        exits,
+        escaped,
+        absorbed,
+    })
 }
 
-fn count_timelines(grid: &[Vec<char>]) -> u64 {
-    if grid.is_empty() {
-        return 0;
+fn print_stats(summary: &BeamSummary, top: usize) {
+    println!("Splits: {}", summary.splits);
+    println!("Timelines: {}", summary.timelines);
+    println!("Escaped: {}", summary.escaped);
+    println!("Absorbed: {}", summary.absorbed);
+
+    println!("Exit columns:");
+    for (col, count) in summary.exits.iter().enumerate().filter(|(_, count)| !count.is_zero()) {
+        println!("  column {}: {}", col + 1, count);
     }
 
-    let rows = grid.len();
-    let cols = grid[0].len();
+    let mut splitters: Vec<(Pos, &Count)> = summary
+        .hits
+        .iter()
+        .filter(|&(pos, _)| summary.splitter_reached[pos])
+        .collect();
+    splitters.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(&b.0)));
+    println!("Most hit splitters:");
+    for ((row, col), count) in splitters.into_iter().take(top) {
+        println!("  row {}, column {}: {}", row + 1, col + 1, count);
+    }
+}
 
-    // Find starting position 'S'
-    let mut start_col = 0;
-    for (r, row) in grid.iter().enumerate() {
-        for (c, &ch) in row.iter().enumerate() {
-            if ch == 'S' {
-                start_col = c;
-                break;
-            }
+fn render_paths(grid: &Grid<char>, summary: &BeamSummary) -> String {
+    let mut overlay = grid.clone();
+    for (pos, cell) in grid.iter() {
+        overlay[pos] = match cell {
+            '^' if summary.splitter_reached[pos] => '*',
+            '.' if summary.visited[pos] => '|',
+            &c => c,
+        };
+    }
+    overlay.render(|&c| c)
+}
+
+// Each cell's timeline count on a log scale from 0 (no beams) to 1 (the busiest cell)
+fn heat_map(occupancy: &Grid<Count>) -> Grid<f64> {
+    let level = |count: &Count| if count.is_zero() { 0.0 } else { count.log2() + 1.0 };
+    let max = occupancy.iter().map(|(_, count)| level(count)).fold(0.0, f64::max);
+    let mut heat = Grid::new(occupancy.width(), occupancy.height(), 0.0);
+    for (pos, count) in occupancy.iter() {
+        if max > 0.0 {
+            heat[pos] = level(count) / max;
         }
     }
+    heat
+}
 
-    // Track number of timelines at each column position
-    // Process row by row
-    let mut timelines: HashMap<usize, u64> = HashMap::new();
-    timelines.insert(start_col, 1);
+fn heat_glyph(t: f64) -> char {
+    const RAMP: &[u8] = b" .:-=+*#%@";
+    if t <= 0.0 {
+        return ' ';
+    }
+    // Any beam at all gets at least the first visible glyph
+    let i = 1 + (t * (RAMP.len() - 2) as f64).round() as usize;
+    RAMP[i.min(RAMP.len() - 1)] as char
+}
 
-    for row in 0..rows - 1 {
-        let mut next_timelines: HashMap<usize, u64> = HashMap::new();
+fn heat_color(t: f64) -> [u8; 3] {
+    if t <= 0.0 {
+        return [16, 16, 24];
+    }
+    // Dark red through orange to pale yellow
+    let channel = |from: f64, to: f64| (from + (to - from) * t).round() as u8;
+    [channel(96.0, 255.0), channel(0.0, 240.0), channel(16.0, 160.0)]
+}
 
-        for (&col, &count) in &timelines {
-            let cell = grid[row + 1][col];
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            match cell {
-                '.' | 'S' => {
-                    // Continue downward, timelines stay the same
-                    *next_timelines.entry(col).or_insert(0) += count;
-                }
-                '^' => {
-                    // Split: each timeline becomes 2 (left and right)
-                    if col > 0 {
-                        *next_timelines.entry(col - 1).or_insert(0) += count;
-                    }
-                    if col + 1 < cols {
-                        *next_timelines.entry(col + 1).or_insert(0) += count;
-                    }
-                }
-                _ => {
-                    // Unknown cell, continue downward
-                    *next_timelines.entry(col).or_insert(0) += count;
-                }
+    const EXAMPLE: &str = "\
+.......S.......
+...............
+.......^.......
+...............
+......^.^......
+...............
+.....^.^.^.....
+...............
+....^.^...^....
+...............
+...^.^...^.^...
+...............
+..^...^.....^..
+...............
+.^.^.^.^.^...^.
+...............
+";
+
+    #[test]
+    fn test_example() {
+        let summary = trace_beams(&Grid::parse(EXAMPLE, |c| c), None).unwrap();
+        assert_eq!(summary.splits, 21);
+        assert_eq!(summary.timelines, Count::Small(40));
+        assert_eq!(summary.hits[(2, 7)], Count::Small(1));
+        assert_eq!(summary.hits[(6, 7)], Count::Small(2));
+        // Reduced counts can be zero, but the splitter was still reached
+        let reduced = trace_beams(&Grid::parse(EXAMPLE, |c| c), Some(2)).unwrap();
+        assert!(reduced.hits[(6, 7)].is_zero());
+        assert!(reduced.splitter_reached[(6, 7)]);
+        assert_eq!(reduced.splits, 21);
+        // The splitter at row 15, column 10 is never reached
+        assert_eq!(EXAMPLE.lines().nth(14).unwrap().as_bytes()[9], b'^');
+        assert!(summary.hits[(14, 9)].is_zero());
+    }
+
+    #[test]
+    fn test_edges() {
+        // Beams split off the side of the grid are lost
+        let summary = trace_beams(&Grid::parse("S.\n^.\n..\n", |c| c), None).unwrap();
+        assert_eq!(summary.splits, 1);
+        assert_eq!(summary.exits, vec![Count::Small(0), Count::Small(1)]);
+        assert_eq!(summary.escaped, Count::Small(1));
+        let empty = trace_beams(&Grid::parse("..\n..\n", |c| c), None).unwrap();
+        assert!(empty.timelines.is_zero());
+    }
+
+    #[test]
+    fn test_cell_types() {
+        let trace = |text: &str| trace_beams(&Grid::parse(text, |c| c), None);
+        let small = Count::Small;
+
+        // Two sources, one absorbed; the mirror turns a beam out of the right edge
+        let summary = trace("S.S.\n#...\n..\\.\n....\n").unwrap();
+        assert_eq!(summary.absorbed, small(1));
+        assert_eq!(summary.escaped, small(1));
+        assert!(summary.timelines.is_zero());
+
+        // A deflector sends the beam sideways onto a mirror that points it back down
+        let summary = trace(".S..\n.>.\\\n....\n").unwrap();
+        assert_eq!(summary.exits, vec![small(0), small(0), small(0), small(1)]);
+
+        // A horizontal beam splits into the rows above and below the splitter
+        let summary = trace("S...\n>.^.\n....\n").unwrap();
+        assert_eq!(summary.splits, 1);
+        assert_eq!(summary.escaped, small(2));
+
+        // Deflectors facing each other bounce the beam forever
+        let error = trace(".S.\n><.\n...\n").err();
+        assert_eq!(error, Some(LoopError { pos: (1, 0) }));
+        assert_eq!(error.unwrap().to_string(), "beam loops forever through row 2, column 1");
+    }
+
+    // A source above `depth` rows of alternating splitters, wide enough that no beam is lost
+    fn splitter_lattice(depth: usize) -> Grid<char> {
+        let width = 2 * depth + 3;
+        let mut text = String::new();
+        for row in 0..=depth {
+            for col in 0..width {
+                let c = match row {
+                    0 if col == depth + 1 => 'S',
+                    0 => '.',
+                    _ if (col + row + depth).is_multiple_of(2) => '^',
+                    _ => '.',
+                };
+                text.push(c);
             }
+            text.push('\n');
         }
+        Grid::parse(&text, |c| c)
+    }
 
-        timelines = next_timelines;
+    #[test]
+    fn test_timelines_beyond_u64() {
+        let lattice = splitter_lattice(100);
+        let summary = trace_beams(&lattice, None).unwrap();
+        assert_eq!(summary.splits, 100 * 101 / 2);
+        assert_eq!(summary.timelines, Count::Big(BigUint::from(2).pow(100)));
+
+        let m = 1_000_000_007;
+        let expected = (0..100).fold(1u64, |acc, _| acc * 2 % m);
+        let reduced = trace_beams(&lattice, Some(m)).unwrap();
+        assert_eq!(reduced.timelines, Count::Small(expected));
+        assert_eq!(reduced.splits, summary.splits);
+        assert_eq!(reduced.splitter_reached, summary.splitter_reached);
+
+        let mut count = Count::Small(u64::MAX);
+        count.add(&Count::Small(1), None);
+        assert_eq!(count.to_string(), "18446744073709551616");
+        assert!(count > Count::Small(u64::MAX));
     }
 
-    // Sum all timelines that reached the bottom
-    timelines.values().sum()
+    #[test]
+    fn test_render_paths() {
+        let grid = Grid::parse("..S..\n.....\n..^..\n.....\n....^\n", |c| c);
+        let summary = trace_beams(&grid, None).unwrap();
+        let expected = "..S..\n..|..\n.|*|.\n.|.|.\n.|.|^\n";
+        assert_eq!(render_paths(&grid, &summary), expected);
+        let reduced = trace_beams(&grid, Some(1)).unwrap();
+        assert_eq!(render_paths(&grid, &reduced), expected);
+        assert_eq!(summary.occupancy[(2, 1)], Count::Small(1));
+
+        let heat = heat_map(&summary.occupancy);
+        assert_eq!(heat[(0, 2)], 1.0);
+        assert_eq!(heat[(4, 4)], 0.0);
+        assert_eq!(heat_glyph(heat[(0, 2)]), '@');
+        assert_eq!(heat_glyph(0.0), ' ');
+        assert_eq!(heat_glyph(0.01), '.');
+
+        // Lattice counts pass 2^64, so the log scale has to handle big counts
+        let summary = trace_beams(&splitter_lattice(70), None).unwrap();
+        assert!((summary.timelines.log2() - 70.0).abs() < 1e-9);
+    }
 }
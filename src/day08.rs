@@ -1,28 +1,74 @@
+use crate::cli;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::fs;
+use std::str::FromStr;
 
-pub fn solve() {
+pub fn run(args: &[String]) {
+    let values = ["--connections", "--largest", "--aggregate", "--format", "--every"];
+    let mode = cli::mode(8, args, &values, &[]);
+    let query = CircuitQuery {
+        connections: cli::parse_option(args, "--connections", 1000),
+        largest: cli::parse_option(args, "--largest", 3),
+        aggregate: cli::parse_option(args, "--aggregate", Aggregate::Product),
+    };
+    match mode {
+        None => solve(&query),
+        Some("dendrogram") => {
+            let points = load_points();
+            let nodes = dendrogram(points.len(), &merge_sequence(&points));
+            let Some(root) = nodes.len().checked_sub(1) else {
+                return;
+            };
+            match cli::option_value(args, "--format").unwrap_or("json") {
+                "json" => println!("{}", to_json(&points, &nodes, root)),
+                "newick" => println!("{};", to_newick(&nodes, root)),
+                format => {
+                    eprintln!("Unknown dendrogram format: {}", format);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some("sizes") => {
+            let every = cli::parse_option(args, "--every", 100).max(1);
+            print!("{}", size_history(&load_points(), every));
+        }
+        Some(mode) => cli::unknown_mode(8, mode),
+    }
+}
+
+fn load_points() -> Vec<Point> {
     let input = fs::read_to_string("inputs/day08.txt").expect("Could not read input file");
+    parse_input(&input).unwrap_or_else(|e| {
+        eprintln!("inputs/day08.txt: {}", e);
+        std::process::exit(1);
+    })
+}
+
+fn solve(query: &CircuitQuery) {
+    let points = load_points();
 
-    let part1 = solve_part1(&input);
+    let part1 = solve_part1(&points, query);
     println!("Part 1: {}", part1);
 
-    let part2 = solve_part2(&input);
+    let part2 = solve_part2(&points);
     println!("Part 2: {}", part2);
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 struct Point {
-    x: f64,
-    y: f64,
-    z: f64,
+    x: i64,
+    y: i64,
+    z: i64,
 }
 
+// Keeps each squared difference at most 2^126, so the sum of three fits in a u128
+const MAX_COORD: i64 = 1 << 62;
+
 impl Point {
-    fn distance_squared(&self, other: &Point) -> f64 {
-        let dx = self.x - other.x;
-        let dy = self.y - other.y;
-        let dz = self.z - other.z;
-        dx * dx + dy * dy + dz * dz
+    fn distance_squared(&self, other: &Point) -> u128 {
+        let square = |a: i64, b: i64| (a as i128 - b as i128).unsigned_abs().pow(2);
+        square(self.x, other.x) + square(self.y, other.y) + square(self.z, other.z)
     }
 }
 
@@ -80,118 +126,521 @@ impl UnionFind {
     }
 }
 
-fn parse_input(input: &str) -> Vec<Point> {
-    input
-        .lines()
-        .filter(|line| !line.is_empty())
-        .map(|line| {
-            let parts: Vec<f64> = line
-                .split(',')
-                .map(|s| s.trim().parse().unwrap())
-                .collect();
-            Point {
-                x: parts[0],
-                y: parts[1],
-                z: parts[2],
+struct ParseError {
+    line: usize,
+    message: String,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+fn parse_input(input: &str) -> Result<Vec<Point>, ParseError> {
+    let mut points = Vec::new();
+    for (idx, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let error = |message: &str| ParseError {
+            line: idx + 1,
+            message: format!("{} \"{}\"", message, line),
+        };
+        let parts = line
+            .split(',')
+            .map(|s| s.trim().parse::<i64>())
+            .collect::<Result<Vec<i64>, _>>()
+            .map_err(|_| error("expected integer coordinates, got"))?;
+        let [x, y, z] = parts[..] else {
+            return Err(error("expected X,Y,Z, got"));
+        };
+        if parts.iter().any(|c| c.unsigned_abs() > MAX_COORD as u64) {
+            return Err(error("coordinate out of range in"));
+        }
+        points.push(Point { x, y, z });
+    }
+    Ok(points)
+}
+
+// A candidate connection, ordered by distance and then by index so ties are deterministic
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Pair {
+    dist_sq: u128,
+    i: usize,
+    j: usize,
+}
+
+// Implicit k-d tree: each subslice of `order` is split at its median on x, y, z in turn
+struct KdTree<'a> {
+    points: &'a [Point],
+    order: Vec<usize>,
+}
+
+fn coord(point: &Point, axis: usize) -> i64 {
+    match axis {
+        0 => point.x,
+        1 => point.y,
+        _ => point.z,
+    }
+}
+
+impl<'a> KdTree<'a> {
+    fn new(points: &'a [Point]) -> Self {
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        Self::build(points, &mut order, 0);
+        KdTree { points, order }
+    }
+
+    fn build(points: &[Point], slice: &mut [usize], axis: usize) {
+        if slice.len() <= 1 {
+            return;
+        }
+        let mid = slice.len() / 2;
+        slice.select_nth_unstable_by(mid, |&a, &b| {
+            coord(&points[a], axis).cmp(&coord(&points[b], axis))
+        });
+        let (left, right) = slice.split_at_mut(mid);
+        Self::build(points, left, (axis + 1) % 3);
+        Self::build(points, &mut right[1..], (axis + 1) % 3);
+    }
+
+    // The k points other than `i` closest to it, by (distance, index)
+    fn nearest(&self, i: usize, k: usize) -> Vec<(u128, usize)> {
+        let mut best: BinaryHeap<Pair> = BinaryHeap::new();
+        self.search(i, k, &self.order, 0, &mut best);
+        best.into_sorted_vec().into_iter().map(|p| (p.dist_sq, p.j)).collect()
+    }
+
+    fn search(
+        &self,
+        i: usize,
+        k: usize,
+        slice: &[usize],
+        axis: usize,
+        best: &mut BinaryHeap<Pair>,
+    ) {
+        if slice.is_empty() || k == 0 {
+            return;
+        }
+        let mid = slice.len() / 2;
+        let node = slice[mid];
+        if node != i {
+            let dist_sq = self.points[i].distance_squared(&self.points[node]);
+            let candidate = Pair { dist_sq, i, j: node };
+            if best.len() < k {
+                best.push(candidate);
+            } else if candidate < *best.peek().unwrap() {
+                best.pop();
+                best.push(candidate);
             }
-        })
-        .collect()
+        }
+
+        let diff = coord(&self.points[i], axis) as i128 - coord(&self.points[node], axis) as i128;
+        let (near, far) = if diff < 0 {
+            (&slice[..mid], &slice[mid + 1..])
+        } else {
+            (&slice[mid + 1..], &slice[..mid])
+        };
+        let next = (axis + 1) % 3;
+        self.search(i, k, near, next, best);
+        // Ties may still beat the worst candidate on index, so only prune strictly farther planes
+        if best.len() < k || diff.unsigned_abs().pow(2) <= best.peek().unwrap().dist_sq {
+            self.search(i, k, far, next, best);
+        }
+    }
+}
+
+// Neighbors j > i of one point, fetched from the tree in doubling batches
+struct NeighborStream {
+    fetched: usize,
+    neighbors: Vec<(u128, usize)>,
+    next: usize,
 }
 
-fn solve_part1(input: &str) -> u64 {
-    let points = parse_input(input);
-    let n = points.len();
+// Yields every pair of points once, closest first, without building the full pair list.
+// Each point keeps a cursor into its own sorted neighbors and a heap merges the cursors.
+struct ClosestPairs<'a> {
+    tree: KdTree<'a>,
+    streams: Vec<NeighborStream>,
+    heap: BinaryHeap<Reverse<Pair>>,
+}
+
+impl<'a> ClosestPairs<'a> {
+    fn new(points: &'a [Point]) -> Self {
+        let mut pairs = ClosestPairs {
+            tree: KdTree::new(points),
+            streams: (0..points.len())
+                .map(|_| NeighborStream { fetched: 0, neighbors: Vec::new(), next: 0 })
+                .collect(),
+            heap: BinaryHeap::new(),
+        };
+        for i in 0..points.len() {
+            pairs.advance(i);
+        }
+        pairs
+    }
 
-    // Calculate all pairwise distances
-    let mut distances: Vec<(f64, usize, usize)> = Vec::new();
-    for i in 0..n {
-        for j in (i + 1)..n {
-            let dist_sq = points[i].distance_squared(&points[j]);
-            distances.push((dist_sq, i, j));
+    // Queue point i's next unseen neighbor, if it has one
+    fn advance(&mut self, i: usize) {
+        let others = self.streams.len() - 1;
+        let stream = &mut self.streams[i];
+        while stream.next == stream.neighbors.len() {
+            if stream.fetched >= others {
+                return;
+            }
+            // Nearest-k results are prefixes of one another, so the cursor stays valid
+            stream.fetched = (stream.fetched * 2).max(8).min(others);
+            stream.neighbors = self.tree.nearest(i, stream.fetched);
+            stream.neighbors.retain(|&(_, j)| j > i);
         }
+        let (dist_sq, j) = stream.neighbors[stream.next];
+        stream.next += 1;
+        self.heap.push(Reverse(Pair { dist_sq, i, j }));
     }
+}
 
-    // Sort by distance (squared is fine for comparison)
-    distances.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+impl Iterator for ClosestPairs<'_> {
+    type Item = Pair;
 
-    // Connect the 1000 closest pairs
-    let mut uf = UnionFind::new(n);
-    for (_, i, j) in distances.iter().take(1000) {
-        uf.union(*i, *j);
+    fn next(&mut self) -> Option<Pair> {
+        let Reverse(pair) = self.heap.pop()?;
+        self.advance(pair.i);
+        Some(pair)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Aggregate {
+    Product,
+    Sum,
+}
+
+impl FromStr for Aggregate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "product" => Ok(Aggregate::Product),
+            "sum" => Ok(Aggregate::Sum),
+            _ => Err(format!("unknown aggregate: {}", s)),
+        }
     }
+}
+
+// Part 1 as a question: connect the closest pairs, then combine the largest circuit sizes
+struct CircuitQuery {
+    connections: usize,
+    largest: usize,
+    aggregate: Aggregate,
+}
 
-    // Get component sizes and find the three largest
+fn solve_part1(points: &[Point], query: &CircuitQuery) -> u128 {
+    // Connect the closest pairs
+    let mut uf = UnionFind::new(points.len());
+    for pair in ClosestPairs::new(points).take(query.connections) {
+        uf.union(pair.i, pair.j);
+    }
+
+    // Get component sizes and find the largest
     let mut sizes = uf.get_component_sizes();
     sizes.sort_by(|a, b| b.cmp(a)); // Sort descending
 
-    // Multiply the three largest
-    let result: u64 = sizes.iter().take(3).map(|&s| s as u64).product();
-    result
+    let largest = sizes.iter().take(query.largest).map(|&s| s as u128);
+    match query.aggregate {
+        Aggregate::Product => largest.product(),
+        Aggregate::Sum => largest.sum(),
+    }
+}
+
+fn solve_part2(points: &[Point]) -> i128 {
+    // The last merge is the connection that unified everything - multiply X coordinates
+    match merge_sequence(points).last() {
+        Some(&Merge { i, j, .. }) => points[i].x as i128 * points[j].x as i128,
+        None => 0,
+    }
+}
+
+// A connection that joined two circuits. `connection` counts every pair tried so far,
+// including those that were already in the same circuit.
+struct Merge {
+    connection: usize,
+    dist_sq: u128,
+    i: usize,
+    j: usize,
 }
 
-fn solve_part2(input: &str) -> u64 {
-    let points = parse_input(input);
-    let n = points.len();
+// Kruskal: connect pairs in order of distance until all are in one circuit. This is also
+// single-linkage clustering, with the merges in order of increasing height.
+fn merge_sequence(points: &[Point]) -> Vec<Merge> {
+    let mut uf = UnionFind::new(points.len());
+    let mut merges = Vec::new();
 
-    // Calculate all pairwise distances
-    let mut distances: Vec<(f64, usize, usize)> = Vec::new();
-    for i in 0..n {
-        for j in (i + 1)..n {
-            let dist_sq = points[i].distance_squared(&points[j]);
-            distances.push((dist_sq, i, j));
+    for (idx, Pair { dist_sq, i, j }) in ClosestPairs::new(points).enumerate() {
+        if merges.len() + 1 >= points.len() {
+            break;
+        }
+        if uf.find(i) != uf.find(j) {
+            uf.union(i, j);
+            merges.push(Merge { connection: idx + 1, dist_sq, i, j });
         }
     }
 
-    // Sort by distance (squared is fine for comparison)
-    distances.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    merges
+}
 
-    // Connect pairs until all are in one circuit
+// Dendrogram nodes: 0..n are the points, and each merge adds a node joining two earlier ones
+enum Node {
+    Leaf(usize),
+    Join { connection: usize, dist_sq: u128, size: usize, left: usize, right: usize },
+}
+
+fn dendrogram(n: usize, merges: &[Merge]) -> Vec<Node> {
+    let mut nodes: Vec<Node> = (0..n).map(Node::Leaf).collect();
     let mut uf = UnionFind::new(n);
-    let mut num_components = n;
-
-    for (_, i, j) in distances.iter() {
-        let root_i = uf.find(*i);
-        let root_j = uf.find(*j);
-
-        if root_i != root_j {
-            uf.union(*i, *j);
-            num_components -= 1;
-
-            // Check if we just unified everything
-            if num_components == 1 {
-                // This is the last connection - multiply X coordinates
-                let x1 = points[*i].x as u64;
-                let x2 = points[*j].x as u64;
-                return x1 * x2;
-            }
+    // Dendrogram node currently representing each union-find root
+    let mut node_of: Vec<usize> = (0..n).collect();
+    let mut sizes = vec![1; n];
+
+    for merge in merges {
+        let (left, right) = (node_of[uf.find(merge.i)], node_of[uf.find(merge.j)]);
+        let size = sizes[left] + sizes[right];
+        uf.union(merge.i, merge.j);
+        node_of[uf.find(merge.i)] = nodes.len();
+        sizes.push(size);
+        nodes.push(Node::Join {
+            connection: merge.connection,
+            dist_sq: merge.dist_sq,
+            size,
+            left,
+            right,
+        });
+    }
+
+    nodes
+}
+
+fn height(node: &Node) -> f64 {
+    match node {
+        Node::Leaf(_) => 0.0,
+        Node::Join { dist_sq, .. } => (*dist_sq as f64).sqrt(),
+    }
+}
+
+// Pending output while walking the dendrogram. The tree can be as deep as there are
+// points, so the walk keeps its own stack rather than recursing.
+enum Step {
+    Visit(usize),
+    Text(&'static str),
+    Length(f64),
+}
+
+fn to_json(points: &[Point], nodes: &[Node], id: usize) -> String {
+    let mut out = String::new();
+    let mut stack = vec![Step::Visit(id)];
+    while let Some(step) = stack.pop() {
+        match step {
+            Step::Visit(id) => match nodes[id] {
+                Node::Leaf(i) => {
+                    let Point { x, y, z } = points[i];
+                    out.push_str(&format!("{{\"id\":{},\"point\":[{},{},{}]}}", id, x, y, z));
+                }
+                Node::Join { connection, dist_sq, size, left, right } => {
+                    out.push_str(&format!(
+                        "{{\"id\":{},\"connection\":{},\"dist_sq\":{},\"size\":{},\"children\":[",
+                        id, connection, dist_sq, size
+                    ));
+                    stack.extend([
+                        Step::Text("]}"),
+                        Step::Visit(right),
+                        Step::Text(","),
+                        Step::Visit(left),
+                    ]);
+                }
+            },
+            Step::Text(text) => out.push_str(text),
+            Step::Length(_) => unreachable!(),
         }
     }
+    out
+}
+
+// Branch lengths are differences in merge distance, so leaves line up at height 0
+fn to_newick(nodes: &[Node], id: usize) -> String {
+    let mut out = String::new();
+    let mut stack = vec![Step::Visit(id)];
+    while let Some(step) = stack.pop() {
+        match step {
+            Step::Visit(id) => match nodes[id] {
+                Node::Leaf(i) => out.push_str(&i.to_string()),
+                Node::Join { left, right, .. } => {
+                    let length = |child: usize| height(&nodes[id]) - height(&nodes[child]);
+                    out.push('(');
+                    stack.extend([
+                        Step::Text(")"),
+                        Step::Length(length(right)),
+                        Step::Visit(right),
+                        Step::Text(","),
+                        Step::Length(length(left)),
+                        Step::Visit(left),
+                    ]);
+                }
+            },
+            Step::Text(text) => out.push_str(text),
+            Step::Length(length) => out.push_str(&format!(":{}", length)),
+        }
+    }
+    out
+}
 
-    0 // Should never reach here if input is valid
+// Tidy CSV of circuit sizes after every `every` connections, and once all are joined
+fn size_history(points: &[Point], every: usize) -> String {
+    let mut out = String::from("connections,size,count\n");
+    let mut uf = UnionFind::new(points.len());
+    let mut components = points.len();
+    let mut snapshot = |uf: &mut UnionFind, connections: usize| {
+        let mut sizes = uf.get_component_sizes();
+        sizes.sort_by(|a, b| b.cmp(a));
+        for group in sizes.chunk_by(|a, b| a == b) {
+            out.push_str(&format!("{},{},{}\n", connections, group[0], group.len()));
+        }
+    };
+
+    snapshot(&mut uf, 0);
+    for (idx, Pair { i, j, .. }) in ClosestPairs::new(points).enumerate() {
+        if components <= 1 {
+            break;
+        }
+        if uf.find(i) != uf.find(j) {
+            uf.union(i, j);
+            components -= 1;
+        }
+        let connections = idx + 1;
+        if connections % every == 0 || components == 1 {
+            snapshot(&mut uf, connections);
+        }
+    }
+    out
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const EXAMPLE: &str = "\
+162,817,812
+57,618,57
+906,360,560
+592,479,940
+352,342,300
+466,668,158
+542,29,236
+431,825,988
+739,650,466
+52,470,668
+216,146,977
+819,987,18
+117,168,530
+805,96,715
+346,949,466
+970,615,88
+941,993,340
+862,61,35
+984,92,344
+425,690,689
+";
+
     #[test]
     fn test_example() {
-        // Example from puzzle: 10 connections result in circuits of size 5, 4, 2
-        // Product = 40
-        let input = "0,0,0
-1,0,0
-2,0,0
-3,0,0
-4,0,0
-10,0,0
-11,0,0
-12,0,0
-13,0,0
-20,0,0
-21,0,0
-";
-        // With 10 connections on 11 points along a line, we'd connect
-        // the closest pairs creating specific circuits
-        // This is a simplified test case
+        // 10 connections leave circuits of sizes 5, 4, 2, 2 and seven single boxes
+        let points = parse_input(EXAMPLE).ok().unwrap();
+        let query = |largest, aggregate| CircuitQuery { connections: 10, largest, aggregate };
+        assert_eq!(solve_part1(&points, &query(3, Aggregate::Product)), 40);
+        assert_eq!(solve_part1(&points, &query(3, Aggregate::Sum)), 11);
+        assert_eq!(solve_part1(&points, &query(20, Aggregate::Sum)), 20);
+        assert_eq!(solve_part2(&points), 25272);
+        assert!("max".parse::<Aggregate>().is_err());
+    }
+
+    #[test]
+    fn test_closest_pairs_order() {
+        // xorshift64 points on a small lattice, so many distances tie
+        let mut state = 88172645463325252u64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % 12) as i64
+        };
+        let points: Vec<Point> =
+            (0..150).map(|_| Point { x: next(), y: next(), z: next() }).collect();
+
+        let mut expected = Vec::new();
+        for i in 0..points.len() {
+            for j in i + 1..points.len() {
+                expected.push(Pair { dist_sq: points[i].distance_squared(&points[j]), i, j });
+            }
+        }
+        expected.sort();
+
+        let lazy: Vec<Pair> = ClosestPairs::new(&points).collect();
+        assert_eq!(lazy, expected);
+        assert_eq!(ClosestPairs::new(&points[..1]).next(), None);
+    }
+
+    #[test]
+    fn test_integer_geometry() {
+        let far = Point { x: -MAX_COORD, y: -MAX_COORD, z: -MAX_COORD };
+        let near = Point { x: MAX_COORD, y: MAX_COORD, z: MAX_COORD };
+        assert_eq!(far.distance_squared(&near), 3 << 126);
+
+        // Equal distances come out in index order
+        let points = parse_input("0,0,0\n2,0,0\n-2,0,0\n").ok().unwrap();
+        let order: Vec<(usize, usize)> = ClosestPairs::new(&points).map(|p| (p.i, p.j)).collect();
+        assert_eq!(order, vec![(0, 1), (0, 2), (1, 2)]);
+
+        let error = |input| parse_input(input).err().unwrap().to_string();
+        assert_eq!(
+            error("1,2,3\n1.5,2,3\n"),
+            "line 2: expected integer coordinates, got \"1.5,2,3\""
+        );
+        assert!(error("0,0,4611686018427387905\n").contains("coordinate out of range"));
+        assert!(error("-9223372036854775808,0,0\n").contains("coordinate out of range"));
+        assert_eq!(error("1,2\n"), "line 1: expected X,Y,Z, got \"1,2\"");
+    }
+
+    #[test]
+    fn test_dendrogram() {
+        let points = parse_input("0,0,0\n1,0,0\n3,0,0\n10,0,0\n").ok().unwrap();
+        let merges = merge_sequence(&points);
+        let heights: Vec<u128> = merges.iter().map(|m| m.dist_sq).collect();
+        assert_eq!(heights, vec![1, 4, 49]);
+        // The pair (0, 2) is tried as the third connection but is already joined
+        assert_eq!(merges.iter().map(|m| m.connection).collect::<Vec<_>>(), vec![1, 2, 4]);
+
+        let nodes = dendrogram(points.len(), &merges);
+        assert_eq!(to_newick(&nodes, 6), "(((0:1,1:1):1,2:2):5,3:7)");
+        assert_eq!(
+            to_json(&points, &nodes, 4),
+            "{\"id\":4,\"connection\":1,\"dist_sq\":1,\"size\":2,\"children\":[\
+             {\"id\":0,\"point\":[0,0,0]},{\"id\":1,\"point\":[1,0,0]}]}"
+        );
+
+        // A chain as deep as the input must not exhaust the call stack
+        let n = 100_000;
+        let mut chain: Vec<Node> = (0..n).map(Node::Leaf).collect();
+        for k in 1..n {
+            let left = if k == 1 { 0 } else { n + k - 2 };
+            let dist_sq = k as u128;
+            chain.push(Node::Join { connection: k, dist_sq, size: k + 1, left, right: k });
+        }
+        let root = chain.len() - 1;
+        assert!(to_newick(&chain, root).starts_with(&"(".repeat(n - 1)));
+        let origins = vec![points[0]; n];
+        assert_eq!(to_json(&origins, &chain, root).matches("\"children\"").count(), n - 1);
+
+        let csv = size_history(&points, 2);
+        assert_eq!(csv, "connections,size,count\n0,1,4\n2,3,1\n2,1,1\n4,4,1\n");
     }
 }
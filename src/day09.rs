@@ -61,7 +61,7 @@ fn solve_part2(input: &str) -> i64 {
     }
 
     // Build polygon edges
-    let mut edges: Vec<((i64, i64), (i64, i64))> = Vec::new();
+    let mut edges: Vec<Edge> = Vec::new();
     for i in 0..n {
         let p1 = tiles[i];
         let p2 = tiles[(i + 1) % n];
@@ -144,8 +144,10 @@ fn solve_part2(input: &str) -> i64 {
     max_area
 }
 
+type Edge = ((i64, i64), (i64, i64));
+
 // Ray casting to determine if point is inside polygon (for axis-aligned edges)
-fn is_inside_polygon(point: (i64, i64), edges: &[((i64, i64), (i64, i64))]) -> bool {
+fn is_inside_polygon(point: (i64, i64), edges: &[Edge]) -> bool {
     let (px, py) = point;
     let mut crossings = 0;
 
@@ -92,13 +92,7 @@ fn solve_machine(machine: &Machine) -> Option<usize> {
 
     for col in 0..n_buttons {
         // Find pivot in this column
-        let mut pivot_row = None;
-        for row in current_row..n_lights {
-            if matrix[row][col] {
-                pivot_row = Some(row);
-                break;
-            }
-        }
+        let pivot_row = (current_row..n_lights).find(|&row| matrix[row][col]);
 
         if let Some(pr) = pivot_row {
             // Swap rows if needed
@@ -109,10 +103,11 @@ fn solve_machine(machine: &Machine) -> Option<usize> {
             pivot_cols.push(col);
 
             // Eliminate all other rows
-            for row in 0..n_lights {
-                if row != current_row && matrix[row][col] {
-                    for c in 0..=n_buttons {
-                        matrix[row][c] ^= matrix[current_row][c];
+            let pivot = matrix[current_row].clone();
+            for (row, cells) in matrix.iter_mut().enumerate() {
+                if row != current_row && cells[col] {
+                    for (cell, &p) in cells.iter_mut().zip(&pivot) {
+                        *cell ^= p;
                     }
                 }
             }
@@ -172,7 +167,7 @@ fn solve_part1(input: &str) -> usize {
 
     machines
         .iter()
-        .filter_map(|machine| solve_machine(machine))
+        .filter_map(solve_machine)
         .sum()
 }
 
@@ -181,7 +176,7 @@ fn solve_part2(input: &str) -> i64 {
 
     machines
         .iter()
-        .filter_map(|machine| solve_joltage(machine))
+        .filter_map(solve_joltage)
         .sum()
 }
 
@@ -217,13 +212,7 @@ fn solve_joltage(machine: &Machine) -> Option<i64> {
 
     for col in 0..n_buttons {
         // Find pivot (non-zero element)
-        let mut pivot_row = None;
-        for row in current_row..n_counters {
-            if matrix[row][col] != 0 {
-                pivot_row = Some(row);
-                break;
-            }
-        }
+        let pivot_row = (current_row..n_counters).find(|&row| matrix[row][col] != 0);
 
         if let Some(pr) = pivot_row {
             if pr != current_row {
@@ -234,12 +223,13 @@ fn solve_joltage(machine: &Machine) -> Option<i64> {
             pivot_vals.push(matrix[current_row][col]);
 
             // Eliminate rows below (but not above for now)
-            for row in (current_row + 1)..n_counters {
-                if matrix[row][col] != 0 {
-                    let pivot_val = matrix[current_row][col];
-                    let factor = matrix[row][col];
-                    for c in 0..=n_buttons {
-                        matrix[row][c] = matrix[row][c] * pivot_val - matrix[current_row][c] * factor;
+            let pivot = matrix[current_row].clone();
+            for cells in matrix.iter_mut().skip(current_row + 1) {
+                if cells[col] != 0 {
+                    let pivot_val = pivot[col];
+                    let factor = cells[col];
+                    for (cell, &p) in cells.iter_mut().zip(&pivot) {
+                        *cell = *cell * pivot_val - p * factor;
                     }
                 }
             }
@@ -284,6 +274,7 @@ fn solve_joltage(machine: &Machine) -> Option<i64> {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn search_free_vars_v2(
     matrix: &[Vec<i64>],
     pivot_cols: &[usize],
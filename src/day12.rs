@@ -1,3 +1,4 @@
+use crate::grid::Grid;
 use std::fs;
 
 pub fn solve() {
@@ -77,7 +78,10 @@ impl Shape {
     }
 }
 
-fn parse_input(input: &str) -> (Vec<Shape>, Vec<(usize, usize, Vec<usize>)>) {
+// (width, height, required count of each shape)
+type Region = (usize, usize, Vec<usize>);
+
+fn parse_input(input: &str) -> (Vec<Shape>, Vec<Region>) {
     let mut shapes = Vec::new();
     let mut regions = Vec::new();
     let lines: Vec<&str> = input.lines().collect();
@@ -139,7 +143,7 @@ fn can_fit_region(
     height: usize,
     required: &[usize],
 ) -> bool {
-    let mut grid = vec![vec![false; width]; height];
+    let mut grid = Grid::new(width, height, false);
     let mut shape_list = Vec::new();
 
     // Build list of shapes to place
@@ -159,12 +163,12 @@ fn can_fit_region(
         return false; // Can't possibly fit
     }
 
-    backtrack(&shapes, &mut grid, &shape_list, 0)
+    backtrack(shapes, &mut grid, &shape_list, 0)
 }
 
 fn backtrack(
     shapes: &[Shape],
-    grid: &mut Vec<Vec<bool>>,
+    grid: &mut Grid<bool>,
     to_place: &[usize],
     idx: usize,
 ) -> bool {
@@ -175,25 +179,10 @@ fn backtrack(
     let shape_idx = to_place[idx];
     let variations = shapes[shape_idx].rotations_and_flips();
 
-    let height = grid.len();
-    let width = if height > 0 { grid[0].len() } else { 0 };
-
     // Find first empty cell (optimization: place shapes left-to-right, top-to-bottom)
-    let mut target_r = None;
-    let mut target_c = None;
-    'outer: for r in 0..height {
-        for c in 0..width {
-            if !grid[r][c] {
-                target_r = Some(r as i32);
-                target_c = Some(c as i32);
-                break 'outer;
-            }
-        }
-    }
-
-    let (target_r, target_c) = match (target_r, target_c) {
-        (Some(r), Some(c)) => (r, c),
-        _ => return true, // Grid is full, all shapes placed
+    let (target_r, target_c) = match grid.find(|&filled| !filled) {
+        Some((r, c)) => (r as i32, c as i32),
+        None => return true, // Grid is full, all shapes placed
     };
 
     // Try each variation of the shape
@@ -215,37 +204,24 @@ fn backtrack(
 
     // If no shape can cover the first empty cell, mark it as a "gap" and continue
     // This handles non-exact packing where not all cells need to be filled
-    grid[target_r as usize][target_c as usize] = true;
+    let target = (target_r as usize, target_c as usize);
+    grid[target] = true;
     let result = backtrack(shapes, grid, to_place, idx);
-    grid[target_r as usize][target_c as usize] = false;
+    grid[target] = false;
     result
 }
 
-fn can_place(grid: &[Vec<bool>], shape: &Shape, row: i32, col: i32) -> bool {
-    let height = grid.len() as i32;
-    let width = if height > 0 { grid[0].len() as i32 } else { 0 };
-
-    for (dr, dc) in &shape.cells {
-        let r = row + dr;
-        let c = col + dc;
-
-        if r < 0 || r >= height || c < 0 || c >= width {
-            return false;
-        }
-
-        if grid[r as usize][c as usize] {
-            return false;
-        }
-    }
-
-    true
+fn can_place(grid: &Grid<bool>, shape: &Shape, row: i32, col: i32) -> bool {
+    shape.cells.iter().all(|(dr, dc)| {
+        grid.pos_at((row + dr) as isize, (col + dc) as isize)
+            .is_some_and(|pos| !grid[pos])
+    })
 }
 
-fn place_shape(grid: &mut [Vec<bool>], shape: &Shape, row: i32, col: i32, mark: bool) {
+fn place_shape(grid: &mut Grid<bool>, shape: &Shape, row: i32, col: i32, mark: bool) {
     for (dr, dc) in &shape.cells {
-        let r = (row + dr) as usize;
-        let c = (col + dc) as usize;
-        grid[r][c] = mark;
+        let pos = ((row + dr) as usize, (col + dc) as usize);
+        grid[pos] = mark;
     }
 }
 
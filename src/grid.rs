@@ -0,0 +1,198 @@
+use std::ops::{Index, IndexMut};
+
+// (row, col)
+pub type Pos = (usize, usize);
+
+pub const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+pub const ADJACENT: [(isize, isize); 8] = [
+    (-1, -1), (-1, 0), (-1, 1),
+    (0, -1),           (0, 1),
+    (1, -1),  (1, 0),  (1, 1),
+];
+
+// Rectangular grid stored row-major in a single Vec
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    // One row per line, mapping each character to a cell. Short lines are padded as if
+    // they ended in spaces, so ragged input still gives a rectangular grid.
+    pub fn parse(text: &str, mut cell: impl FnMut(char) -> T) -> Self {
+        let lines: Vec<&str> = text.lines().collect();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for line in &lines {
+            let len = line.chars().count();
+            cells.extend(line.chars().map(&mut cell));
+            cells.extend((len..width).map(|_| cell(' ')));
+        }
+
+        Grid {
+            width,
+            height: lines.len(),
+            cells,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[allow(dead_code)]
+    pub fn get(&self, (row, col): Pos) -> Option<&T> {
+        if row < self.height && col < self.width {
+            Some(&self.cells[row * self.width + col])
+        } else {
+            None
+        }
+    }
+
+    // Bounds-checked conversion from signed coordinates
+    pub fn pos_at(&self, row: isize, col: isize) -> Option<Pos> {
+        if row >= 0 && col >= 0 && (row as usize) < self.height && (col as usize) < self.width {
+            Some((row as usize, col as usize))
+        } else {
+            None
+        }
+    }
+
+    pub fn offset(&self, (row, col): Pos, (dr, dc): (isize, isize)) -> Option<Pos> {
+        self.pos_at(row as isize + dr, col as isize + dc)
+    }
+
+    // Offset with toroidal wrap-around at the edges
+    pub fn wrapping_offset(&self, (row, col): Pos, (dr, dc): (isize, isize)) -> Pos {
+        (
+            (row as isize + dr).rem_euclid(self.height as isize) as usize,
+            (col as isize + dc).rem_euclid(self.width as isize) as usize,
+        )
+    }
+
+    // In-bounds neighbors of pos for an arbitrary set of offsets
+    #[allow(dead_code)]
+    pub fn neighbors<'a>(
+        &'a self,
+        pos: Pos,
+        offsets: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = Pos> + 'a {
+        offsets.iter().filter_map(move |&d| self.offset(pos, d))
+    }
+
+    #[allow(dead_code)]
+    pub fn neighbors4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.neighbors(pos, &ORTHOGONAL)
+    }
+
+    #[allow(dead_code)]
+    pub fn neighbors8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.neighbors(pos, &ADJACENT)
+    }
+
+    // All positions in row-major order
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.width * self.height).map(move |i| (i / width, i % width))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    // First matching position in row-major order
+    pub fn find(&self, mut pred: impl FnMut(&T) -> bool) -> Option<Pos> {
+        self.iter().find(|(_, cell)| pred(cell)).map(|(pos, _)| pos)
+    }
+
+    pub fn render(&self, mut glyph: impl FnMut(&T) -> char) -> String {
+        let mut out = String::with_capacity((self.width + 1) * self.height);
+        for row in self.cells.chunks(self.width.max(1)) {
+            out.extend(row.iter().map(&mut glyph));
+            out.push('\n');
+        }
+        out
+    }
+
+    // Binary PPM (P6) image with each cell drawn as a scale x scale block
+    pub fn to_ppm(&self, scale: usize, mut color: impl FnMut(&T) -> [u8; 3]) -> Vec<u8> {
+        let scale = scale.max(1);
+        let (w, h) = (self.width * scale, self.height * scale);
+        let mut out = format!("P6\n{} {}\n255\n", w, h).into_bytes();
+        out.reserve(w * h * 3);
+        for row in self.cells.chunks(self.width.max(1)) {
+            let pixels: Vec<[u8; 3]> = row.iter().map(&mut color).collect();
+            for _ in 0..scale {
+                for pixel in &pixels {
+                    for _ in 0..scale {
+                        out.extend_from_slice(pixel);
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): Pos) -> &T {
+        assert!(row < self.height && col < self.width, "position out of bounds");
+        &self.cells[row * self.width + col]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, (row, col): Pos) -> &mut T {
+        assert!(row < self.height && col < self.width, "position out of bounds");
+        &mut self.cells[row * self.width + col]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_neighbors() {
+        let grid = Grid::parse("S.#\n.#\n", |c| c);
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(1, 2)], ' ');
+        assert_eq!(grid.find(|&c| c == '#'), Some((0, 2)));
+        assert_eq!(grid.get((2, 0)), None);
+
+        let corner: Vec<Pos> = grid.neighbors8((0, 0)).collect();
+        assert_eq!(corner, vec![(0, 1), (1, 0), (1, 1)]);
+        let middle: Vec<Pos> = grid.neighbors4((1, 1)).collect();
+        assert_eq!(middle, vec![(0, 1), (1, 0), (1, 2)]);
+        let knight: Vec<Pos> = grid.neighbors((0, 0), &[(1, 2), (2, 1)]).collect();
+        assert_eq!(knight, vec![(1, 2)]);
+        assert_eq!(grid.wrapping_offset((0, 0), (-1, -1)), (1, 2));
+
+        assert_eq!(grid.render(|&c| if c == ' ' { '.' } else { c }), "S.#\n.#.\n");
+
+        let ppm = grid.to_ppm(2, |&c| if c == '#' { [255, 0, 0] } else { [0, 0, 0] });
+        assert!(ppm.starts_with(b"P6\n6 4\n255\n"));
+        assert_eq!(ppm.len(), 11 + 6 * 4 * 3);
+    }
+}
@@ -0,0 +1,278 @@
+use std::fmt::Debug;
+
+// Integer types an IntervalSet can hold. Intervals are inclusive, so neighbors and
+// lengths need checked arithmetic at the type's limits.
+pub trait Endpoint: Copy + Ord + Debug {
+    fn succ(self) -> Option<Self>;
+    fn pred(self) -> Option<Self>;
+    // Number of values in start..=end
+    fn count(start: Self, end: Self) -> u128;
+}
+
+impl Endpoint for u64 {
+    fn succ(self) -> Option<Self> {
+        self.checked_add(1)
+    }
+
+    fn pred(self) -> Option<Self> {
+        self.checked_sub(1)
+    }
+
+    fn count(start: Self, end: Self) -> u128 {
+        (end - start) as u128 + 1
+    }
+}
+
+// Inclusive (start, end)
+pub type Interval<T> = (T, T);
+
+// Set of integers stored as sorted, disjoint, non-adjacent inclusive intervals
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    intervals: Vec<(T, T)>,
+}
+
+impl<T: Endpoint> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Endpoint> IntervalSet<T> {
+    pub fn new() -> Self {
+        IntervalSet { intervals: Vec::new() }
+    }
+
+    // Adds start..=end, merging with any intervals it overlaps or touches
+    #[allow(dead_code)]
+    pub fn insert(&mut self, start: T, end: T) {
+        assert!(start <= end, "interval start {:?} is after end {:?}", start, end);
+
+        // First interval that ends at or after start - 1 could touch the new one
+        let first = self
+            .intervals
+            .partition_point(|&(_, e)| e.succ().is_some_and(|after| after < start));
+        // Intervals starting at or before end + 1 could touch it too
+        let last = self
+            .intervals
+            .partition_point(|&(s, _)| end.succ().is_none_or(|after| s <= after));
+
+        let (mut new_start, mut new_end) = (start, end);
+        if first < last {
+            new_start = new_start.min(self.intervals[first].0);
+            new_end = new_end.max(self.intervals[last - 1].1);
+        }
+        self.intervals.splice(first..last, [(new_start, new_end)]);
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.find(value).is_some()
+    }
+
+    // The interval containing value, by binary search
+    pub fn find(&self, value: T) -> Option<(T, T)> {
+        let idx = self.intervals.partition_point(|&(_, e)| e < value);
+        self.intervals.get(idx).copied().filter(|&(s, _)| s <= value)
+    }
+
+    // Closest interval ending before value and closest one starting after it, for a value
+    // that isn't in the set (a contained value has no such neighbors)
+    pub fn neighbors(&self, value: T) -> (Option<Interval<T>>, Option<Interval<T>>) {
+        if self.contains(value) {
+            return (None, None);
+        }
+        let idx = self.intervals.partition_point(|&(_, e)| e < value);
+        let before = idx.checked_sub(1).map(|i| self.intervals[i]);
+        (before, self.intervals.get(idx).copied())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (T, T)> + '_ {
+        self.intervals.iter().copied()
+    }
+
+    // Number of disjoint intervals after merging
+    pub fn interval_count(&self) -> usize {
+        self.intervals.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    // Total number of values in the set
+    pub fn total_len(&self) -> u128 {
+        self.intervals.iter().map(|&(s, e)| T::count(s, e)).sum()
+    }
+
+    // Holes between consecutive intervals, as inclusive ranges
+    pub fn gaps(&self) -> impl Iterator<Item = (T, T)> + '_ {
+        self.intervals
+            .windows(2)
+            .map(|w| (w[0].1.succ().unwrap(), w[1].0.pred().unwrap()))
+    }
+
+    #[allow(dead_code)]
+    pub fn union(&self, other: &Self) -> Self {
+        let mut all: Vec<(T, T)> = self.iter().chain(other.iter()).collect();
+        all.sort();
+        all.into_iter().collect()
+    }
+
+    #[allow(dead_code)]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let (a, b) = (&self.intervals, &other.intervals);
+        let mut out = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            let start = a[i].0.max(b[j].0);
+            let end = a[i].1.min(b[j].1);
+            if start <= end {
+                out.push((start, end));
+            }
+            // Drop whichever interval finishes first
+            if a[i].1 < b[j].1 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { intervals: out }
+    }
+
+    #[allow(dead_code)]
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut out = Vec::new();
+        let mut j = 0;
+        for &(start, end) in &self.intervals {
+            let mut cursor = Some(start);
+            // Skip holes that end before this interval
+            while j < other.intervals.len() && other.intervals[j].1 < start {
+                j += 1;
+            }
+            let mut k = j;
+            while let Some(from) = cursor {
+                match other.intervals.get(k) {
+                    Some(&(hole_start, hole_end)) if hole_start <= end => {
+                        if from < hole_start {
+                            out.push((from, hole_start.pred().unwrap()));
+                        }
+                        cursor = hole_end.succ().filter(|&next| next <= end);
+                        k += 1;
+                    }
+                    _ => {
+                        out.push((from, end));
+                        cursor = None;
+                    }
+                }
+            }
+        }
+        IntervalSet { intervals: out }
+    }
+}
+
+impl<T: Endpoint> FromIterator<(T, T)> for IntervalSet<T> {
+    // Sorts once and merges in a single pass, cheaper than repeated inserts
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        let mut ranges: Vec<(T, T)> = iter.into_iter().collect();
+        ranges.sort();
+
+        let mut intervals: Vec<(T, T)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            assert!(start <= end, "interval start {:?} is after end {:?}", start, end);
+            match intervals.last_mut() {
+                Some(last) if last.1.succ().is_none_or(|after| start <= after) => {
+                    last.1 = last.1.max(end);
+                }
+                _ => intervals.push((start, end)),
+            }
+        }
+        IntervalSet { intervals }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Universe 0..BITS, sets represented as bitmasks
+    const BITS: u64 = 7;
+
+    fn from_mask(mask: u32) -> IntervalSet<u64> {
+        // Insert runs in reverse so insert has to merge on both sides
+        let mut set = IntervalSet::new();
+        for v in (0..BITS).rev().filter(|&v| mask >> v & 1 == 1) {
+            set.insert(v, v);
+        }
+        set
+    }
+
+    fn to_mask(set: &IntervalSet<u64>) -> u32 {
+        set.iter().flat_map(|(s, e)| s..=e).fold(0, |m, v| m | 1 << v)
+    }
+
+    fn normalized(set: &IntervalSet<u64>) -> bool {
+        set.intervals.iter().all(|&(s, e)| s <= e)
+            && set.intervals.windows(2).all(|w| w[0].1 + 1 < w[1].0)
+    }
+
+    #[test]
+    fn test_against_bitset_oracle() {
+        let all = 1u32 << BITS;
+        for a in 0..all {
+            let set_a = from_mask(a);
+            assert!(normalized(&set_a));
+            assert_eq!(to_mask(&set_a), a);
+            assert_eq!(set_a.total_len(), a.count_ones() as u128);
+            for v in 0..BITS + 1 {
+                assert_eq!(set_a.contains(v), a >> v & 1 == 1);
+            }
+
+            let gaps = set_a.gaps().flat_map(|(s, e)| s..=e).fold(0u32, |m, v| m | 1 << v);
+            let hull = match (set_a.iter().next(), set_a.iter().last()) {
+                (Some((lo, _)), Some((_, hi))) => (lo..=hi).fold(0u32, |m, v| m | 1 << v),
+                _ => 0,
+            };
+            assert_eq!(gaps, hull & !a);
+
+            for start in 0..BITS {
+                for end in start..BITS {
+                    let mut inserted = set_a.clone();
+                    inserted.insert(start, end);
+                    let range = (start..=end).fold(0u32, |m, v| m | 1 << v);
+                    assert!(normalized(&inserted));
+                    assert_eq!(to_mask(&inserted), a | range);
+                }
+            }
+
+            for b in 0..all {
+                let set_b = from_mask(b);
+                for (result, expected) in [
+                    (set_a.union(&set_b), a | b),
+                    (set_a.intersection(&set_b), a & b),
+                    (set_a.difference(&set_b), a & !b),
+                ] {
+                    assert!(normalized(&result));
+                    assert_eq!(to_mask(&result), expected, "a={:b} b={:b}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_extremes() {
+        let mut set: IntervalSet<u64> = [(u64::MAX - 1, u64::MAX), (0, 0)].into_iter().collect();
+        set.insert(1, 5);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![(0, 5), (u64::MAX - 1, u64::MAX)]);
+        assert_eq!(set.total_len(), 8);
+        assert!(set.contains(u64::MAX));
+        assert_eq!(set.gaps().next(), Some((6, u64::MAX - 2)));
+        assert_eq!(set.neighbors(9), (Some((0, 5)), Some((u64::MAX - 1, u64::MAX))));
+        assert_eq!(set.neighbors(3), (None, None));
+
+        let full: IntervalSet<u64> = [(0, u64::MAX)].into_iter().collect();
+        assert_eq!(full.total_len(), 1 << 64);
+        assert_eq!(full.difference(&set).iter().collect::<Vec<_>>(), vec![(6, u64::MAX - 2)]);
+        assert!(set.difference(&full).is_empty());
+    }
+}
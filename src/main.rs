@@ -1,3 +1,5 @@
+mod bigint;
+mod cli;
 mod day01;
 mod day02;
 mod day03;
@@ -10,6 +12,8 @@ mod day09;
 mod day10;
 mod day11;
 mod day12;
+mod grid;
+mod interval;
 
 use std::env;
 
@@ -17,9 +21,10 @@ fn main() {
     let args: Vec<String> = env::args().collect();
 
     if args.len() < 2 {
-        eprintln!("Usage: {} <day_number|all>", args[0]);
+        eprintln!("Usage: {} <day_number|all> [mode] [options]", args[0]);
         eprintln!("  day_number: 1-25 to run a specific day");
         eprintln!("  all: run all available puzzles");
+        eprintln!("  mode: optional extra output for a day, e.g. `2 report --limit 5`");
         std::process::exit(1);
     }
 
@@ -29,7 +34,7 @@ fn main() {
         run_all();
     } else {
         match arg.parse::<u32>() {
-            Ok(day) => run_day(day),
+            Ok(day) => run_day(day, &args[2..]),
             Err(_) => {
                 eprintln!("Invalid argument: {}. Expected a day number (1-25) or 'all'", arg);
                 std::process::exit(1);
@@ -38,48 +43,63 @@ fn main() {
     }
 }
 
-fn run_day(day: u32) {
+fn run_day(day: u32, extra: &[String]) {
     println!("=== Day {:02} ===", day);
     match day {
-        1 => day01::solve(),
-        2 => day02::solve(),
-        3 => day03::solve(),
-        4 => day04::solve(),
-        5 => day05::solve(),
-        6 => day06::solve(),
-        7 => day07::solve(),
-        8 => day08::solve(),
-        9 => day09::solve(),
-        10 => day10::solve(),
-        11 => day11::solve(),
-        12 => day12::solve(),
+        1 => {
+            cli::no_options(1, extra);
+            day01::solve()
+        }
+        2 => day02::run(extra),
+        3 => day03::run(extra),
+        4 => day04::run(extra),
+        5 => day05::run(extra),
+        6 => day06::run(extra),
+        7 => day07::run(extra),
+        8 => day08::run(extra),
+        9 => {
+            cli::no_options(9, extra);
+            day09::solve()
+        }
+        10 => {
+            cli::no_options(10, extra);
+            day10::solve()
+        }
+        11 => {
+            cli::no_options(11, extra);
+            day11::solve()
+        }
+        12 => {
+            cli::no_options(12, extra);
+            day12::solve()
+        }
         _ => eprintln!("Day {} not implemented yet", day),
     }
 }
 
 fn run_all() {
     println!("Running all implemented puzzles...\n");
-    run_day(1);
+    run_day(1, &[]);
     println!();
-    run_day(2);
+    run_day(2, &[]);
     println!();
-    run_day(3);
+    run_day(3, &[]);
     println!();
-    run_day(4);
+    run_day(4, &[]);
     println!();
-    run_day(5);
+    run_day(5, &[]);
     println!();
-    run_day(6);
+    run_day(6, &[]);
     println!();
-    run_day(7);
+    run_day(7, &[]);
     println!();
-    run_day(8);
+    run_day(8, &[]);
     println!();
-    run_day(9);
+    run_day(9, &[]);
     println!();
-    run_day(10);
+    run_day(10, &[]);
     println!();
-    run_day(11);
+    run_day(11, &[]);
     println!();
-    run_day(12);
+    run_day(12, &[]);
 }